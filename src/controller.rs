@@ -1,17 +1,25 @@
-use crate::search::{check_calc, check_system_commands, get_apps, AppItem};
+use crate::config::{find_override, load_custom_overrides, BrowserRule};
+use crate::i18n::{tr, trf};
+use crate::history::{history_key, HistorySource, HistoryStore};
+use crate::search::{check_calc, check_system_commands, get_apps, score_item, window_match_keys, AppItem};
 use crate::ui::{create_hotkeys_window, update_visuals, EditingMode, LauncherState, SelectionMode};
-use crate::modules::app_launcher::update_list_view;
+use crate::modules::app_launcher::{cancel_confirmation, request_confirmation, update_list_view};
 use crate::modules::file_search::check_files;
-use crate::modules::web_search::check_web;
-use crate::modules::app_edit::handle_app_edit;
-use crate::utils::{hsv_to_rgb, rgb_to_hsv};
+use crate::modules::web_search::{browser_choices, browser_command, cached_suggestions, check_web, curl_provider, fetch_suggestions, link_url, search_target, suggestion_items};
+use crate::modules::run_command::check_run;
+use crate::modules::history_view::check_history;
+use crate::modules::browser_data::{browser_entries, check_browser};
+use crate::modules::categories::{check_categories, first_selectable, is_section, next_selectable};
+use crate::modules::path_index::get_path_apps;
+use crate::modules::app_edit::{handle_app_edit, override_id, start_new_entry, start_override_edit, toggle_override_flag, update_pin, OverrideFlag, PinAction};
+use crate::modules::power_menu::{activate_power_option, cancel_pending_power};
+use crate::utils::{hsv_to_rgb, rgb_to_hsv, shell_quote};
 use crate::modules::color_picker::update_color_ui;
 use fuzzy_matcher::skim::SkimMatcherV2;
-use fuzzy_matcher::FuzzyMatcher;
 use gtk4::prelude::*;
 use gtk4::{ApplicationWindow, Box, EventControllerKey, PropagationPhase, ScrolledWindow};
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::process::Command;
 use std::rc::Rc;
 use std::sync::LazyLock;
@@ -60,6 +68,20 @@ pub fn setup_search_logic(
             }
             return;
         }
+        if sh.mode == SelectionMode::History {
+            let known: Vec<&AppItem> = sh.all_apps.iter().chain(sh.commands.iter()).chain(sh.path_apps.iter()).collect();
+            sh.filtered_apps = check_history(&text, &sh.history, &known, &sh.power_options);
+            sh.app_index = 0;
+            let filtered = sh.filtered_apps.clone();
+            let config = sh.theme_config.clone();
+            drop(sh);
+            update_list_view(&container_c, &filtered, 0, &window_c, &state_c, &config);
+            scroll_c.vadjustment().set_value(0.0);
+            if let Ok(sh_re) = state_c.try_borrow() {
+                update_visuals(&container_c, &scroll_c, &p_box_c, &sh_re);
+            }
+            return;
+        }
         let mut results = Vec::new();
         if text.starts_with('#') || text.starts_with("rgb(") || text.starts_with("rgba(") {
             sh.mode = SelectionMode::Color;
@@ -120,55 +142,96 @@ pub fn setup_search_logic(
         }
         if text == "hotkeys?" {
             results.push(AppItem {
-                name: "Show Hotkeys Help".to_string(),
+                name: tr("Show Hotkeys Help"),
                 exec: "SHOW_HOTKEYS".to_string(),
                 terminal: false,
                 icon: "\u{f030c}".to_string(),
                 desktop_id: "internal".to_string(),
                 system_icon: None,
+                ..Default::default()
             });
         }
-        if let Some(calc) = check_calc(&text) {
-            results.push(calc);
-        }
-        
-        let file_results = check_files(&text, &matcher, &sh.all_apps);
-        if text.starts_with('/') || text.starts_with('~') {
-            results.extend(file_results);
-            results.extend(check_web(&text, &sh.history, &URL_RE, &sh.theme_config.search_engine));
+        let run_prefix = sh.theme_config.run_prefix.clone();
+        if !run_prefix.is_empty() && text.starts_with(&run_prefix) {
+            results.extend(check_run(&text[run_prefix.len()..], &sh.history));
+        } else if let Some(cat_query) = text.strip_prefix('@') {
+            results.extend(check_categories(cat_query, &sh.all_apps, &sh.history, &matcher));
         } else {
-            results.extend(check_web(&text, &sh.history, &URL_RE, &sh.theme_config.search_engine));
-            results.extend(file_results);
-        }
-        
-        results.extend(check_system_commands(&text, &sh.power_options));
-        let mut matches: Vec<(i64, AppItem)> = sh.all_apps
-            .iter()
-            .filter_map(|app| matcher.fuzzy_match(&app.name, &text).map(|sc| (sc, app.clone())))
-            .collect();
-        matches.sort_by(|(s1, a), (s2, b)| {
-            s2.cmp(s1).then_with(|| {
-                let h_a = sh.history.get(&a.exec).unwrap_or(&0);
-                let h_b = sh.history.get(&b.exec).unwrap_or(&0);
-                h_b.cmp(h_a)
-            })
-        });
-        results.extend(matches.into_iter().map(|(_, a)| a));
-        if text.is_empty() {
-            results = sh.all_apps.clone();
-            results.sort_by(|a, b| {
-                let h_a = sh.history.get(&a.exec).unwrap_or(&0);
-                let h_b = sh.history.get(&b.exec).unwrap_or(&0);
-                h_b.cmp(h_a)
+            if let Some(calc) = check_calc(&text) {
+                results.push(calc);
+            }
+
+            let file_results = check_files(&text, &matcher, &sh.all_apps);
+            if text.starts_with('/') || text.starts_with('~') {
+                results.extend(file_results);
+                results.extend(check_web(&text, &sh.history, &URL_RE, &sh.theme_config.search_engines, &sh.theme_config.search_engine));
+            } else {
+                results.extend(check_web(&text, &sh.history, &URL_RE, &sh.theme_config.search_engines, &sh.theme_config.search_engine));
+                results.extend(file_results);
+            }
+
+            let mut browser_matches = Vec::new();
+            let wants_browser = text.starts_with(':') || (sh.theme_config.browser_results && text.chars().count() >= 2 && !text.starts_with(['/', '~', '?', '!']));
+            if sh.theme_config.browser_data && wants_browser {
+                let entry = e.clone();
+                let loaded = browser_entries(move || entry.emit_by_name::<()>("changed", &[]));
+                if let Some(entries) = loaded {
+                    if let Some(sub) = text.strip_prefix(':') {
+                        let known: HashSet<String> = results.iter().map(|r| r.exec.clone()).collect();
+                        results.extend(check_browser(sub, &entries, &matcher, 30).into_iter().map(|(_, a)| a).filter(|a| !known.contains(&a.exec)));
+                    } else {
+                        browser_matches = check_browser(&text, &entries, &matcher, 5);
+                    }
+                }
+            }
+
+            let mut matches: Vec<(i64, AppItem)> = sh.all_apps
+                .iter()
+                .chain(sh.commands.iter())
+                .filter_map(|app| score_item(&matcher, app, &text).map(|sc| (sc, app.clone())))
+                .collect();
+            matches.extend(sh.path_apps.iter().filter_map(|app| score_item(&matcher, app, &text).map(|sc| (sc / 2, app.clone()))));
+            matches.extend(check_system_commands(&text, &sh.power_options, &matcher));
+            matches.extend(browser_matches.into_iter().map(|(sc, a)| (sc / 3, a)));
+            matches.sort_by(|(s1, a), (s2, b)| {
+                s2.cmp(s1).then_with(|| {
+                    sh.history.count_item(b).cmp(&sh.history.count_item(a))
+                })
             });
+            results.extend(matches.into_iter().map(|(_, a)| a));
+            let config = &sh.theme_config;
+            let target = search_target(&text, &config.search_engines, &config.search_engine).filter(|(_, q, _)| !q.is_empty() && config.search_suggestions);
+            if let Some((engine, q, url)) = target.and_then(|(engine, q, _)| engine.suggest_url_for(q).map(|url| (engine, q, url))) {
+                match cached_suggestions(&url) {
+                    Some(list) => {
+                        let at = results.iter().position(|r| r.desktop_id == "web").map(|i| i + 1).unwrap_or(0);
+                        results.splice(at..at, suggestion_items(engine, q, &list));
+                    }
+                    None => {
+                        let (entry, query, timeout) = (e.clone(), text.clone(), config.suggest_timeout);
+                        glib::timeout_add_local_once(std::time::Duration::from_millis(150), move || {
+                            if entry.text() != query { return; }
+                            fetch_suggestions(curl_provider, url, timeout, move || {
+                                if entry.text() == query {
+                                    entry.emit_by_name::<()>("changed", &[]);
+                                }
+                            });
+                        });
+                    }
+                }
+            }
         }
+        if text.is_empty() {
+            results = sh.default_list();
+        }
+        sh.app_index = first_selectable(&results);
         sh.filtered_apps = results;
-        sh.app_index = 0;
         sh.mode = SelectionMode::Apps;
         let filtered = sh.filtered_apps.clone();
         let config = sh.theme_config.clone();
+        let sel = sh.app_index;
         drop(sh);
-        update_list_view(&container_c, &filtered, 0, &window_c, &state_c, &config);
+        update_list_view(&container_c, &filtered, sel, &window_c, &state_c, &config);
         scroll_c.vadjustment().set_value(0.0);
         if let Ok(sh_re) = state_c.try_borrow() {
             update_visuals(&container_c, &scroll_c, &p_box_c, &sh_re);
@@ -202,7 +265,8 @@ pub fn setup_key_controller(
     controller.set_propagation_phase(PropagationPhase::Capture);
     let check_hk = |name: &str, key: gtk4::gdk::Key, mods: gtk4::gdk::ModifierType, hotkeys: &HashMap<String, crate::config::Hotkey>| -> bool {
         if let Some(hk) = hotkeys.get(name) {
-            if let Some(hk_key) = gtk4::gdk::Key::from_name(&hk.key) {
+            let capitalized = hk.key.chars().next().map(|c| c.to_uppercase().chain(hk.key.chars().skip(1)).collect::<String>()).unwrap_or_default();
+            if let Some(hk_key) = gtk4::gdk::Key::from_name(&hk.key).or_else(|| gtk4::gdk::Key::from_name(&capitalized)) {
                 let clean_mods = mods & (gtk4::gdk::ModifierType::CONTROL_MASK | 
                                        gtk4::gdk::ModifierType::ALT_MASK | 
                                        gtk4::gdk::ModifierType::SHIFT_MASK | 
@@ -214,7 +278,7 @@ pub fn setup_key_controller(
                         target_mods = (target_mods & !gtk4::gdk::ModifierType::SUPER_MASK) | gtk4::gdk::ModifierType::META_MASK;
                     }
                 }
-                return key == hk_key && clean_mods == target_mods;
+                return key.to_lower() == hk_key.to_lower() && clean_mods == target_mods;
             }
         }
         false
@@ -226,20 +290,70 @@ pub fn setup_key_controller(
                 if key == gtk4::gdk::Key::Escape {
                     drop(sh_test);
                     let mut sh = st.borrow_mut();
-                    sh.editing_mode = EditingMode::None; sh.editing_id = None;
+                    sh.editing_mode = EditingMode::None; sh.editing_id = None; sh.new_entry = None;
                     pop.set_visible(false); e.grab_focus(); return glib::Propagation::Stop;
                 }
-                if key == gtk4::gdk::Key::Return {
+                if matches!(key, gtk4::gdk::Key::Return | gtk4::gdk::Key::KP_Enter) {
                     drop(sh_test);
-                    handle_app_edit(&st, &w, &c, &ee, &pop);
-                    e.grab_focus();
+                    if handle_app_edit(&st, &w, &c, &el, &ee, &pop) {
+                        e.grab_focus();
+                    }
                     return glib::Propagation::Stop;
                 }
                 return glib::Propagation::Proceed;
             }
         }
         let mut sh = st.borrow_mut();
+        if sh.pending_power.is_some() && !matches!(key, gtk4::gdk::Key::Return | gtk4::gdk::Key::KP_Enter) {
+            cancel_pending_power(&mut sh);
+            if key == gtk4::gdk::Key::Escape {
+                update_visuals(&c, &s, &p, &sh);
+                return glib::Propagation::Stop;
+            }
+        }
+        let shift = modifier.contains(gtk4::gdk::ModifierType::SHIFT_MASK);
+        let confirm_key = if sh.pending_clear { key == gtk4::gdk::Key::Delete && shift } else { matches!(key, gtk4::gdk::Key::Return | gtk4::gdk::Key::KP_Enter) };
+        if sh.pending_confirm.is_some() && !confirm_key {
+            cancel_confirmation(&mut sh);
+            let config = sh.theme_config.clone();
+            update_list_view(&c, &sh.filtered_apps, sh.app_index, &w, &st, &config);
+            update_visuals(&c, &s, &p, &sh);
+            if key == gtk4::gdk::Key::Escape {
+                return glib::Propagation::Stop;
+            }
+        }
         if key == gtk4::gdk::Key::Escape { drop(sh); w.close(); return glib::Propagation::Stop; }
+        if key == gtk4::gdk::Key::Delete && sh.mode == SelectionMode::History {
+            let keys: Vec<_> = if shift {
+                if !sh.pending_clear {
+                    let idx = sh.app_index;
+                    let Some(name) = sh.filtered_apps.get(idx).map(|a| a.name.clone()) else { return glib::Propagation::Stop; };
+                    sh.filtered_apps[idx].name = trf("Delete {} history entries? Press Shift+Delete again", &[&sh.filtered_apps.len().to_string()]);
+                    sh.pending_confirm = Some((idx, name));
+                    sh.pending_clear = true;
+                    let (apps, config) = (sh.filtered_apps.clone(), sh.theme_config.clone());
+                    drop(sh);
+                    update_list_view(&c, &apps, idx, &w, &st, &config);
+                    return glib::Propagation::Stop;
+                }
+                cancel_confirmation(&mut sh);
+                sh.filtered_apps.iter().map(|a| a.history_key()).collect()
+            } else {
+                sh.filtered_apps.get(sh.app_index).map(|a| a.history_key()).into_iter().collect()
+            };
+            for k in &keys {
+                sh.history.remove(k);
+            }
+            let text = e.text().to_string();
+            let idx = sh.app_index;
+            drop(sh);
+            e.set_text(&text);
+            if let Ok(mut sh) = st.try_borrow_mut() {
+                sh.app_index = idx.min(sh.filtered_apps.len().saturating_sub(1));
+                update_visuals(&c, &s, &p, &sh);
+            }
+            return glib::Propagation::Stop;
+        }
         if key == gtk4::gdk::Key::Delete {
             let exec_to_remove = match sh.mode {
                 SelectionMode::Apps => sh.filtered_apps.get(sh.app_index).map(|a| (a.exec.clone(), a.desktop_id.clone())),
@@ -248,7 +362,7 @@ pub fn setup_key_controller(
             };
             if let Some((exec, did)) = exec_to_remove {
                 if did == "web" || did == "file" || did == "clipboard" {
-                    sh.history.remove(&exec);
+                    sh.history.remove(&history_key(Some(&did), &exec));
                     let text = e.text().to_string();
                     drop(sh);
                     e.set_text(&text);
@@ -257,58 +371,49 @@ pub fn setup_key_controller(
             }
         }
         if check_hk("hk-toggle-hidden", key, modifier, &sh.hotkeys) || (key == gtk4::gdk::Key::h && modifier.contains(gtk4::gdk::ModifierType::CONTROL_MASK)) {
-            sh.show_hidden = !sh.show_hidden; sh.all_apps = get_apps(sh.show_hidden);
-            sh.filtered_apps = sh.all_apps.clone(); sh.app_index = 0;
+            sh.show_hidden = !sh.show_hidden; sh.all_apps = get_apps(sh.show_hidden, &sh.theme_config);
+            if sh.theme_config.path_index { sh.path_apps = get_path_apps(&sh.all_apps, sh.show_hidden); }
+            sh.filtered_apps = sh.default_list(); sh.app_index = 0;
             let config = sh.theme_config.clone();
             update_list_view(&c, &sh.filtered_apps, 0, &w, &st, &config);
             update_visuals(&c, &s, &p, &sh); return glib::Propagation::Stop;
         }
         if (check_hk("hk-rename", key, modifier, &sh.hotkeys) || (key == gtk4::gdk::Key::r && modifier.contains(gtk4::gdk::ModifierType::CONTROL_MASK))) && sh.mode == SelectionMode::Apps {
-            let app_to_edit = sh.filtered_apps.get(sh.app_index).map(|a| (a.name.clone(), a.desktop_id.clone()));
+            let app_to_edit = sh.filtered_apps.get(sh.app_index).and_then(|a| override_id(a).map(|id| (a.name.clone(), id)));
             if let Some((name, id)) = app_to_edit {
-                if !["internal", "file", "web", "clipboard"].contains(&id.as_str()) {
-                    sh.editing_mode = EditingMode::Rename;
-                    sh.editing_id = Some(id);
-                    el.set_text("Rename App");
-                    pop.set_visible(true);
-                    ee.remove_css_class("mono-text");
-                    ee.set_text(&name);
-                    ee.select_region(0, -1);
-                    ee.grab_focus();
+                sh.editing_mode = EditingMode::Rename;
+                sh.editing_id = Some(id);
+                el.set_text(&tr("Rename App"));
+                pop.set_visible(true);
+                ee.remove_css_class("mono-text");
+                ee.set_text(&name);
+                ee.select_region(0, -1);
+                ee.grab_focus();
+                return glib::Propagation::Stop;
+            }
+        }
+        if check_hk("hk-add-entry", key, modifier, &sh.hotkeys) && sh.mode == SelectionMode::Apps {
+            start_new_entry(&mut sh, &el, &ee, &pop);
+            return glib::Propagation::Stop;
+        }
+        if sh.mode == SelectionMode::Apps {
+            for (hk, action) in [("hk-pin", PinAction::Toggle), ("hk-pin-up", PinAction::Up), ("hk-pin-down", PinAction::Down)] {
+                if check_hk(hk, key, modifier, &sh.hotkeys) {
+                    if update_pin(&mut sh, action, e.text().is_empty()) {
+                        let config = sh.theme_config.clone();
+                        update_list_view(&c, &sh.filtered_apps, sh.app_index, &w, &st, &config);
+                        update_visuals(&c, &s, &p, &sh);
+                    }
                     return glib::Propagation::Stop;
                 }
             }
-        }
-        if (check_hk("hk-icon", key, modifier, &sh.hotkeys) || (key == gtk4::gdk::Key::e && modifier.contains(gtk4::gdk::ModifierType::CONTROL_MASK))) && sh.mode == SelectionMode::Apps {
-            let app_to_edit = sh.filtered_apps.get(sh.app_index).map(|a| (a.icon.clone(), a.desktop_id.clone()));
-            if let Some((icon, id)) = app_to_edit {
-                if !["internal", "file", "web", "clipboard"].contains(&id.as_str()) {
-                    sh.editing_mode = EditingMode::Icon;
-                    sh.editing_id = Some(id);
-                    el.set_text("Edit Icon");
-                    pop.set_visible(true);
-                    ee.add_css_class("mono-text");
-                    let current_icon = if sh.theme_config.icon_mode == "system" { "".to_string() } else { icon };
-                    ee.set_text(&current_icon);
-                    ee.select_region(0, -1);
-                    ee.grab_focus();
+            for (hk, mode) in [("hk-keywords", EditingMode::Keywords), ("hk-args", EditingMode::Args), ("hk-env", EditingMode::Env), ("hk-workdir", EditingMode::Workdir)] {
+                if check_hk(hk, key, modifier, &sh.hotkeys) && start_override_edit(&mut sh, mode, &el, &ee, &pop) {
                     return glib::Propagation::Stop;
                 }
             }
-        }
-        if (check_hk("hk-hide-app", key, modifier, &sh.hotkeys) || (key == gtk4::gdk::Key::s && modifier.contains(gtk4::gdk::ModifierType::CONTROL_MASK))) && sh.mode == SelectionMode::Apps {
-            let app_id = sh.filtered_apps.get(sh.app_index).map(|a| a.desktop_id.clone());
-            if let Some(id) = app_id {
-                if !["internal", "file", "web", "clipboard"].contains(&id.as_str()) {
-                    let mut overrides = crate::config::load_custom_overrides();
-                    let ent = overrides.entry(id).or_default();
-                    
-                    ent.hidden = Some(!sh.show_hidden);
-                    
-                    crate::config::save_custom_overrides(&overrides);
-                    sh.all_apps = get_apps(sh.show_hidden);
-                    sh.filtered_apps = sh.all_apps.clone();
-                    sh.app_index = sh.app_index.min(sh.filtered_apps.len().saturating_sub(1));
+            for (hk, flag) in [("hk-toggle-terminal", OverrideFlag::Terminal), ("hk-new-instance", OverrideFlag::NewInstance)] {
+                if check_hk(hk, key, modifier, &sh.hotkeys) && toggle_override_flag(&mut sh, flag) {
                     let config = sh.theme_config.clone();
                     update_list_view(&c, &sh.filtered_apps, sh.app_index, &w, &st, &config);
                     update_visuals(&c, &s, &p, &sh);
@@ -316,10 +421,44 @@ pub fn setup_key_controller(
                 }
             }
         }
+        if (check_hk("hk-icon", key, modifier, &sh.hotkeys) || (key == gtk4::gdk::Key::e && modifier.contains(gtk4::gdk::ModifierType::CONTROL_MASK))) && sh.mode == SelectionMode::Apps {
+            let app_to_edit = sh.filtered_apps.get(sh.app_index).and_then(|a| override_id(a).map(|id| (a.icon.clone(), id)));
+            if let Some((icon, id)) = app_to_edit {
+                sh.editing_mode = EditingMode::Icon;
+                sh.editing_id = Some(id);
+                el.set_text(&tr("Edit Icon"));
+                pop.set_visible(true);
+                ee.add_css_class("mono-text");
+                let current_icon = if sh.theme_config.icon_mode == "system" { "".to_string() } else { icon };
+                ee.set_text(&current_icon);
+                ee.select_region(0, -1);
+                ee.grab_focus();
+                return glib::Propagation::Stop;
+            }
+        }
+        if (check_hk("hk-hide-app", key, modifier, &sh.hotkeys) || (key == gtk4::gdk::Key::s && modifier.contains(gtk4::gdk::ModifierType::CONTROL_MASK))) && sh.mode == SelectionMode::Apps {
+            let app_id = sh.filtered_apps.get(sh.app_index).and_then(override_id);
+            if let Some(id) = app_id {
+                let mut overrides = crate::config::load_custom_overrides();
+                let ent = overrides.entry(id).or_default();
+                
+                ent.hidden = Some(!sh.show_hidden);
+                
+                crate::config::save_custom_overrides(&overrides);
+                sh.all_apps = get_apps(sh.show_hidden, &sh.theme_config);
+                if sh.theme_config.path_index { sh.path_apps = get_path_apps(&sh.all_apps, sh.show_hidden); }
+                sh.filtered_apps = sh.default_list();
+                sh.app_index = sh.app_index.min(sh.filtered_apps.len().saturating_sub(1));
+                let config = sh.theme_config.clone();
+                update_list_view(&c, &sh.filtered_apps, sh.app_index, &w, &st, &config);
+                update_visuals(&c, &s, &p, &sh);
+                return glib::Propagation::Stop;
+            }
+        }
         if key == gtk4::gdk::Key::i && modifier.contains(gtk4::gdk::ModifierType::CONTROL_MASK) {
             sh.icon_mode = if sh.icon_mode == "system" { "nerd".to_string() } else { "system".to_string() };
             crate::config::save_icon_mode(&sh.icon_mode);
-            sh.all_apps = get_apps(sh.show_hidden);
+            sh.all_apps = get_apps(sh.show_hidden, &sh.theme_config);
             sh.theme_config.icon_mode = sh.icon_mode.clone();
             let config = sh.theme_config.clone();
             update_list_view(&c, &sh.filtered_apps, sh.app_index, &w, &st, &config);
@@ -329,7 +468,7 @@ pub fn setup_key_controller(
         if check_hk("hk-clipboard", key, modifier, &sh.hotkeys) {
             if sh.mode == SelectionMode::Clipboard {
                 sh.mode = SelectionMode::Apps;
-                sh.filtered_apps = sh.all_apps.clone();
+                sh.filtered_apps = sh.default_list();
                 sh.app_index = 0;
                 e.set_placeholder_text(None);
             } else {
@@ -337,7 +476,7 @@ pub fn setup_key_controller(
                 sh.clipboard_items = crate::search::get_clipboard_history();
                 sh.filtered_apps = sh.clipboard_items.clone();
                 sh.clip_index = 0;
-                e.set_placeholder_text(Some("Clipboard Search..."));
+                e.set_placeholder_text(Some(&tr("Clipboard Search...")));
             }
             e.set_text("");
             let config = sh.theme_config.clone();
@@ -345,10 +484,36 @@ pub fn setup_key_controller(
             update_visuals(&c, &s, &p, &sh);
             return glib::Propagation::Stop;
         }
+        if check_hk("hk-history", key, modifier, &sh.hotkeys) || (key == gtk4::gdk::Key::Tab && sh.mode == SelectionMode::History) {
+            if sh.mode == SelectionMode::History {
+                sh.mode = SelectionMode::Apps;
+                sh.filtered_apps = sh.default_list();
+                e.set_placeholder_text(None);
+            } else {
+                sh.mode = SelectionMode::History;
+                let known: Vec<&AppItem> = sh.all_apps.iter().chain(sh.commands.iter()).chain(sh.path_apps.iter()).collect();
+                sh.filtered_apps = check_history("", &sh.history, &known, &sh.power_options);
+                e.set_placeholder_text(Some(&tr("History (web: file: app: >30d)...")));
+            }
+            sh.app_index = 0;
+            e.set_text("");
+            let config = sh.theme_config.clone();
+            update_list_view(&c, &sh.filtered_apps, 0, &w, &st, &config);
+            update_visuals(&c, &s, &p, &sh);
+            return glib::Propagation::Stop;
+        }
+        if check_hk("hk-incognito", key, modifier, &sh.hotkeys) {
+            let paused = !sh.history.is_paused();
+            sh.history.set_paused(paused);
+            if paused { e.add_css_class("incognito"); } else { e.remove_css_class("incognito"); }
+            let msg = if paused { "History recording paused for this session" } else { "History recording resumed" };
+            let _ = Command::new("notify-send").arg(tr("Incognito")).arg(tr(msg)).spawn();
+            return glib::Propagation::Stop;
+        }
         if check_hk("hk-color-picker", key, modifier, &sh.hotkeys) || (key == gtk4::gdk::Key::g && modifier.contains(gtk4::gdk::ModifierType::CONTROL_MASK)) {
             if sh.mode == SelectionMode::Color {
                 sh.mode = SelectionMode::Apps;
-                sh.filtered_apps = sh.all_apps.clone();
+                sh.filtered_apps = sh.default_list();
                 sh.app_index = 0;
                 let config = sh.theme_config.clone();
                 update_list_view(&c, &sh.filtered_apps, 0, &w, &st, &config);
@@ -392,6 +557,7 @@ pub fn setup_key_controller(
                 SelectionMode::Power => SelectionMode::Apps,
                 SelectionMode::Clipboard => SelectionMode::Apps,
                 SelectionMode::Color => SelectionMode::Apps,
+                SelectionMode::History => SelectionMode::Apps,
             };
             if sh.mode == SelectionMode::Power { sh.power_index = 0; }
             if sh.mode == SelectionMode::Apps { e.set_placeholder_text(None); }
@@ -414,6 +580,10 @@ pub fn setup_key_controller(
                             display_path.push('/');
                         }
                         path_to_set = Some(display_path);
+                    } else if let Some(cmd) = app.exec.strip_prefix("RUN:").filter(|_| app.desktop_id == "run") {
+                        path_to_set = Some(format!("{}{} ", sh.theme_config.run_prefix, cmd));
+                    } else if let Some(kw) = app.exec.strip_prefix("ENGINE:") {
+                        path_to_set = Some(format!("?{} ", kw));
                     }
                 }
                 if let Some(p) = path_to_set {
@@ -460,8 +630,8 @@ pub fn setup_key_controller(
             }
         }
         if key == gtk4::gdk::Key::Down {
-            if sh.mode == SelectionMode::Apps && sh.app_index + 1 < sh.filtered_apps.len() {
-                sh.app_index += 1;
+            if let Some(next) = next_selectable(&sh.filtered_apps, sh.app_index, true).filter(|_| matches!(sh.mode, SelectionMode::Apps | SelectionMode::History)) {
+                sh.app_index = next;
                 update_visuals(&c, &s, &p, &sh);
             } else if sh.mode == SelectionMode::Clipboard && sh.clip_index + 1 < sh.filtered_apps.len() {
                 sh.clip_index += 1;
@@ -470,8 +640,8 @@ pub fn setup_key_controller(
             return glib::Propagation::Stop;
         }
         if key == gtk4::gdk::Key::Up {
-            if sh.mode == SelectionMode::Apps && sh.app_index > 0 {
-                sh.app_index -= 1;
+            if let Some(prev) = next_selectable(&sh.filtered_apps, sh.app_index, false).filter(|_| matches!(sh.mode, SelectionMode::Apps | SelectionMode::History)) {
+                sh.app_index = prev;
                 update_visuals(&c, &s, &p, &sh);
             } else if sh.mode == SelectionMode::Clipboard && sh.clip_index > 0 {
                 sh.clip_index -= 1;
@@ -479,19 +649,51 @@ pub fn setup_key_controller(
             }
             return glib::Propagation::Stop;
         }
-        if key == gtk4::gdk::Key::Return {
+        if matches!(key, gtk4::gdk::Key::Return | gtk4::gdk::Key::KP_Enter) && modifier.contains(gtk4::gdk::ModifierType::SHIFT_MASK) && sh.mode == SelectionMode::Apps {
+            let choices = sh.filtered_apps.get(sh.app_index).and_then(link_url).map(browser_choices);
+            if let Some(choices) = choices.filter(|c| !c.is_empty()) {
+                sh.filtered_apps = choices;
+                sh.app_index = 0;
+                let config = sh.theme_config.clone();
+                update_list_view(&c, &sh.filtered_apps, 0, &w, &st, &config);
+                update_visuals(&c, &s, &p, &sh);
+                return glib::Propagation::Stop;
+            }
+        }
+        if matches!(key, gtk4::gdk::Key::Return | gtk4::gdk::Key::KP_Enter) {
             if sh.mode == SelectionMode::Color {
                 let (h, s, v) = sh.current_hsv;
                 let (r, g, b) = hsv_to_rgb(h, s, v);
                 let hex = format!("#{:02X}{:02X}{:02X}{:02X}", (r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8, (sh.current_alpha * 255.0) as u8);
                 let fol = sh.theme_config.focus_on_launch;
                 let term_cmd = sh.theme_config.terminal.clone();
-                launch_app(&format!("COPY:{}", hex), false, &mut sh.history, Some("color"), fol, &term_cmd);
+                launch_app(&format!("COPY:{}", hex), false, &mut sh.history, Some("color"), fol, &term_cmd, &[]);
                 drop(sh); w.close(); return glib::Propagation::Stop;
             }
+            if sh.mode == SelectionMode::Power {
+                let idx = sh.power_index;
+                drop(sh);
+                activate_power_option(idx, &st, &w, false);
+                if let Ok(sh_re) = st.try_borrow() {
+                    update_visuals(&c, &s, &p, &sh_re);
+                }
+                return glib::Propagation::Stop;
+            }
+            if matches!(sh.mode, SelectionMode::Apps | SelectionMode::History) {
+                let idx = sh.app_index;
+                drop(sh);
+                if request_confirmation(&c, &w, &st, idx) {
+                    if let Ok(sh_re) = st.try_borrow() {
+                        update_visuals(&c, &s, &p, &sh_re);
+                    }
+                    return glib::Propagation::Stop;
+                }
+                sh = st.borrow_mut();
+                sh.pending_confirm = None;
+            }
             let action = match sh.mode {
-                SelectionMode::Apps => sh.filtered_apps.get(sh.app_index).map(|a| (Some(a.exec.clone()), a.terminal, false, Some(a.desktop_id.clone()))),
-                SelectionMode::Power => if sh.power_index < sh.power_options.len() { Some((Some(sh.power_options[sh.power_index].command.clone()), false, true, None)) } else { None },
+                SelectionMode::Apps | SelectionMode::History => sh.filtered_apps.get(sh.app_index).filter(|a| !is_section(a) && !a.exec.is_empty()).map(|a| (Some(a.exec.clone()), a.terminal, false, Some(a.desktop_id.clone()))),
+                SelectionMode::Power => None,
                 SelectionMode::Clipboard => sh.filtered_apps.get(sh.clip_index).map(|a| (Some(a.exec.clone()), false, false, None)),
                 SelectionMode::Color => None,
             };
@@ -501,13 +703,28 @@ pub fn setup_key_controller(
                         let app_ref = w.application().expect("App error"); drop(sh);
                         create_hotkeys_window(&app_ref, &st); return glib::Propagation::Stop;
                     }
+                    if let Some(kw) = e_str.strip_prefix("ENGINE:") {
+                        drop(sh);
+                        e.set_text(&format!("?{} ", kw));
+                        e.set_position(-1);
+                        return glib::Propagation::Stop;
+                    }
+                    if let Some(idx) = e_str.strip_prefix("POWER:").and_then(|i| i.parse().ok()) {
+                        drop(sh);
+                        activate_power_option(idx, &st, &w, true);
+                        if let Ok(sh_re) = st.try_borrow() {
+                            update_visuals(&c, &s, &p, &sh_re);
+                        }
+                        return glib::Propagation::Stop;
+                    }
                 }
                 let fol = sh.theme_config.focus_on_launch;
                 let term_cmd = sh.theme_config.terminal.clone();
+                let rules = sh.theme_config.browser_rules.clone();
                 drop(sh);
                 if let Some(exec) = ex {
                     let mut guard = st.borrow_mut();
-                    launch_app(&exec, trm, &mut guard.history, did.as_deref(), fol, &term_cmd);
+                    launch_app(&exec, trm, &mut guard.history, did.as_deref(), fol, &term_cmd, &rules);
                 }
                 w.close();
             }
@@ -541,9 +758,20 @@ pub fn setup_window_events(window: &ApplicationWindow, state: &Rc<RefCell<Launch
     let st = state.clone();
     window.connect_close_request(move |win| {
         let s = st.borrow();
+        let pos = if s.layer_shell {
+            s.window_pos
+        } else {
+            s.wm.launcher_position().map(|(x, y)| (x - s.output_origin.0, y - s.output_origin.1))
+        };
+        let (x, y) = pos.unwrap_or((-1, -1));
+        let mut outputs = s.outputs.clone();
+        if !s.output_key.is_empty() {
+            outputs.insert(s.output_key.clone(), crate::config::OutputGeometry { width: win.width(), height: win.height(), x, y });
+        }
         crate::config::save_state(&crate::config::WindowState {
-            width: win.width(), height: win.height(), x: 0, y: 0,
+            width: win.width(), height: win.height(), x, y,
             history: s.history.clone(), show_hidden: s.show_hidden, show_hotkeys: s.show_hotkeys,
+            outputs,
         });
         glib::Propagation::Proceed
     });
@@ -576,12 +804,17 @@ pub fn setup_window_events(window: &ApplicationWindow, state: &Rc<RefCell<Launch
     let st_wm = state.clone();
     glib::timeout_add_local(std::time::Duration::from_millis(400), move || {
         if let Ok(s) = st_wm.try_borrow() {
-            s.wm.center_cursor_or_window();
+            if !s.layer_shell {
+                match (s.theme_config.window_placement.as_str(), s.window_pos) {
+                    ("center", _) | (_, None) => s.wm.center_cursor_or_window(),
+                    (_, Some((x, y))) => s.wm.move_launcher(s.output_origin.0 + x, s.output_origin.1 + y),
+                }
+            }
         }
         glib::ControlFlow::Break
     });
 }
-pub fn launch_app(exec: &str, terminal: bool, history: &mut HashMap<String, u32>, desktop_id: Option<&str>, focus_on_launch: bool, terminal_cmd: &str) {
+pub fn launch_app(exec: &str, terminal: bool, history: &mut HistoryStore, desktop_id: Option<&str>, focus_on_launch: bool, terminal_cmd: &str, browser_rules: &[BrowserRule]) {
     let wm = crate::wm::detect();
     if exec.is_empty() { return; }
     if exec.starts_with("CLIPBOARD_SET:") {
@@ -599,36 +832,73 @@ pub fn launch_app(exec: &str, terminal: bool, history: &mut HashMap<String, u32>
         } else {
             "Copied"
         };
-        let _ = Command::new("notify-send").arg(title).arg(val).spawn();
+        let _ = Command::new("notify-send").arg(tr(title)).arg(val).spawn();
+        return;
+    }
+    if exec.starts_with("SESSION:") {
+        history.record(history_key(desktop_id, exec));
+        crate::modules::power_menu::run_power_command(exec);
+        return;
+    }
+    if let Some((app_id, url)) = exec.strip_prefix("OPEN_WITH:").and_then(|rest| rest.split_once(' ')) {
+        history.record((HistorySource::Web, url.to_string()));
+        if let Some(app) = gio::DesktopAppInfo::new(app_id) {
+            let _ = app.launch_uris(&[url], gio::AppLaunchContext::NONE);
+        }
+        return;
+    }
+    if let Some(cmd) = exec.strip_prefix("RUN:") {
+        history.record(history_key(desktop_id, exec));
+        let shell_cmd = if terminal {
+            let hold = format!("{}; exec \"${{SHELL:-sh}}\"", cmd);
+            format!("setsid {} -e sh -c {} >/dev/null 2>&1 &", terminal_cmd, shell_quote(&hold))
+        } else {
+            format!("setsid sh -c {} >/dev/null 2>&1 &", shell_quote(cmd))
+        };
+        let _ = Command::new("sh").arg("-c").arg(shell_cmd).spawn();
         return;
     }
     let clean_exec = if exec.starts_with("xdg-open ") { exec.trim_start_matches("xdg-open ").trim_matches('"') }
                     else if exec.starts_with("OPEN_PATH:") { &exec[10..] }
                     else { exec };
-    if focus_on_launch {
+    let override_key = if desktop_id == Some("path") { Some(exec) } else { desktop_id };
+    let custom = override_key.and_then(|k| find_override(&load_custom_overrides(), k).cloned()).unwrap_or_default();
+    if focus_on_launch && !custom.new_instance.unwrap_or(false) {
         if let Some(did) = desktop_id {
-            if did != "file" && did != "web" && did != "calc" {
-                let windows = wm.get_window_list();
-                let (did_lower, did_base) = (did.to_lowercase(), did.to_lowercase().trim_end_matches(".desktop").to_string());
-                for win in windows {
-                    let app_id = win.app_id.to_lowercase();
-                    let title = win.title.to_lowercase();
-                    if app_id == did_base || app_id == did_lower || title.contains(&did_base) || (!app_id.is_empty() && did_base.contains(&app_id)) {
-                        wm.focus_window(&win.id);
-                        *history.entry(clean_exec.to_string()).or_insert(0) += 1;
-                        return;
-                    }
+            if did != "file" && did != "web" && did != "calc" && did != "command" {
+                let keys = window_match_keys(did, exec);
+                if crate::wm::focus_app_window(&*wm, &keys) {
+                    history.record(history_key(desktop_id, exec));
+                    return;
                 }
             }
         }
     }
     if exec.starts_with("xdg-open ") || exec.starts_with("OPEN_PATH:") {
-        let _ = Command::new("xdg-open").arg(clean_exec).spawn();
-        *history.entry(clean_exec.to_string()).or_insert(0) += 1;
+        match browser_command(browser_rules, clean_exec).filter(|_| exec.starts_with("xdg-open ")) {
+            Some(cmd) => { let _ = Command::new("sh").arg("-c").arg(format!("setsid {} >/dev/null 2>&1 &", cmd)).spawn(); }
+            None => { let _ = Command::new("xdg-open").arg(clean_exec).spawn(); }
+        }
+        history.record(history_key(desktop_id, exec));
         return;
     }
-    *history.entry(clean_exec.to_string()).or_insert(0) += 1;
-    let cmd = exec.replace("%f","").replace("%F","").replace("%u","").replace("%U","").replace("%d","").replace("%D","").replace("%n","").replace("%N","").replace("%i","").replace("%c","").replace("%k","");
-    let shell_cmd = if terminal { format!("setsid {} -e {} >/dev/null 2>&1 &", terminal_cmd, cmd.trim()) } else { format!("setsid {} >/dev/null 2>&1 &", cmd.trim()) };
+    history.record(history_key(desktop_id, exec));
+    let mut cmd = if desktop_id == Some("command") { exec.to_string() } else { exec.replace("%f","").replace("%F","").replace("%u","").replace("%U","").replace("%d","").replace("%D","").replace("%n","").replace("%N","").replace("%i","").replace("%c","").replace("%k","") };
+    if let Some(args) = custom.args.as_deref().filter(|a| !a.is_empty()) {
+        cmd = format!("{} {}", cmd.trim(), args);
+    }
+    if let Some(env) = custom.env.as_ref().filter(|e| !e.is_empty()) {
+        let mut vars: Vec<String> = env.iter().map(|(k, v)| format!("{}={}", k, shell_quote(v))).collect();
+        vars.sort();
+        cmd = format!("env {} {}", vars.join(" "), cmd.trim());
+    }
+    let cd = custom.workdir.as_deref().filter(|d| !d.is_empty()).map(|d| {
+        let dir = match d.strip_prefix('~') {
+            Some(rest) => format!("{}{}", std::env::var("HOME").unwrap_or_default(), rest),
+            None => d.to_string(),
+        };
+        format!("cd {} && ", shell_quote(&dir))
+    }).unwrap_or_default();
+    let shell_cmd = if terminal { format!("{}setsid {} -e {} >/dev/null 2>&1 &", cd, terminal_cmd, cmd.trim()) } else { format!("{}setsid {} >/dev/null 2>&1 &", cd, cmd.trim()) };
     let _ = Command::new("sh").arg("-c").arg(shell_cmd).spawn();
 }
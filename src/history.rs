@@ -0,0 +1,301 @@
+use crate::i18n::tr;
+use crate::search::AppItem;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum HistorySource {
+    App,
+    Web,
+    File,
+    Run,
+    Command,
+    Session,
+    Other,
+}
+impl HistorySource {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "app" | "apps" => Some(Self::App),
+            "web" => Some(Self::Web),
+            "file" | "files" => Some(Self::File),
+            "run" => Some(Self::Run),
+            "command" | "commands" | "cmd" => Some(Self::Command),
+            "session" | "power" => Some(Self::Session),
+            "other" => Some(Self::Other),
+            _ => None,
+        }
+    }
+    pub fn label(self) -> String {
+        tr(match self {
+            Self::App => "App",
+            Self::Web => "Web",
+            Self::File => "File",
+            Self::Run => "Run",
+            Self::Command => "Command",
+            Self::Session => "Session",
+            Self::Other => "Other",
+        })
+    }
+}
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HistoryEntry {
+    pub source: HistorySource,
+    pub id: String,
+    pub count: u32,
+    #[serde(default)]
+    pub last_used: u64,
+}
+pub type HistoryKey = (HistorySource, String);
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HistoryRepr {
+    Entries(Vec<HistoryEntry>),
+    Legacy(HashMap<String, u32>),
+}
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(from = "HistoryRepr", into = "Vec<HistoryEntry>")]
+pub struct HistoryStore {
+    entries: HashMap<HistoryKey, HistoryEntry>,
+    paused: bool,
+    ignored: Vec<HistorySource>,
+}
+impl From<HistoryRepr> for HistoryStore {
+    fn from(repr: HistoryRepr) -> Self {
+        let mut store = HistoryStore::default();
+        match repr {
+            HistoryRepr::Entries(entries) => {
+                for mut e in entries {
+                    if e.last_used == 0 { e.last_used = now(); }
+                    store.entries.insert((e.source, e.id.clone()), e);
+                }
+            }
+            HistoryRepr::Legacy(map) => {
+                for (key, count) in map {
+                    let (source, id) = legacy_key(&key);
+                    let entry = store.entry(source, id);
+                    entry.count += count;
+                    entry.last_used = now();
+                }
+            }
+        }
+        store
+    }
+}
+impl From<HistoryStore> for Vec<HistoryEntry> {
+    fn from(store: HistoryStore) -> Self {
+        let mut entries: Vec<HistoryEntry> = store.entries.into_values().collect();
+        entries.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.id.cmp(&b.id)));
+        entries
+    }
+}
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+pub fn parse_age(s: &str) -> Option<u64> {
+    let s = s.trim().to_lowercase();
+    let unit = match s.chars().last()? {
+        's' => 1,
+        'm' => 60,
+        'h' => 3600,
+        'd' => 86400,
+        'w' => 7 * 86400,
+        'y' => 365 * 86400,
+        _ => return None,
+    };
+    let value: u64 = s[..s.len() - 1].parse().ok()?;
+    Some(value * unit).filter(|&secs| secs > 0)
+}
+fn legacy_key(key: &str) -> HistoryKey {
+    if let Some(target) = key.strip_prefix("xdg-open ") {
+        let target = target.trim_matches('"');
+        return if target.starts_with('/') { (HistorySource::File, target.to_string()) } else { (HistorySource::Web, target.to_string()) };
+    }
+    if let Some(cmd) = key.strip_prefix("RUN:") {
+        return (HistorySource::Run, cmd.to_string());
+    }
+    if let Some(action) = key.strip_prefix("SESSION:") {
+        return (HistorySource::Session, action.to_string());
+    }
+    if key.starts_with("http://") || key.starts_with("https://") {
+        return (HistorySource::Web, key.to_string());
+    }
+    if key.starts_with('/') && std::path::Path::new(key).exists() {
+        return (HistorySource::File, key.to_string());
+    }
+    (HistorySource::App, key.to_string())
+}
+pub fn history_key(desktop_id: Option<&str>, exec: &str) -> HistoryKey {
+    let target = exec.trim_start_matches("xdg-open ").trim_start_matches("OPEN_PATH:").trim_matches('"');
+    if let Some(cmd) = exec.strip_prefix("RUN:") {
+        return (HistorySource::Run, cmd.to_string());
+    }
+    if let Some(action) = exec.strip_prefix("SESSION:") {
+        return (HistorySource::Session, action.to_string());
+    }
+    match desktop_id.unwrap_or_default() {
+        "web" => (HistorySource::Web, target.to_string()),
+        "file" => (HistorySource::File, target.to_string()),
+        "command" => (HistorySource::Command, exec.to_string()),
+        "path" => (HistorySource::App, exec.to_string()),
+        "" | "internal" | "clipboard" | "system" | "calc" | "color" | "section" => (HistorySource::Other, exec.to_string()),
+        id => (HistorySource::App, id.to_string()),
+    }
+}
+impl HistoryStore {
+    fn entry(&mut self, source: HistorySource, id: String) -> &mut HistoryEntry {
+        self.entries.entry((source, id.clone())).or_insert(HistoryEntry { source, id, count: 0, last_used: 0 })
+    }
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+    pub fn set_ignored(&mut self, ignored: Vec<HistorySource>) {
+        self.ignored = ignored;
+    }
+    pub fn record(&mut self, key: HistoryKey) {
+        if self.paused || self.ignored.contains(&key.0) { return; }
+        let entry = self.entry(key.0, key.1);
+        entry.count += 1;
+        entry.last_used = now();
+    }
+    pub fn count(&self, key: &HistoryKey) -> u32 {
+        self.entries.get(key).map(|e| e.count).unwrap_or(0)
+    }
+    pub fn count_item(&self, item: &AppItem) -> u32 {
+        self.count(&item.history_key())
+    }
+    pub fn remove(&mut self, key: &HistoryKey) {
+        self.entries.remove(key);
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.entries.values()
+    }
+    pub fn iter_source(&self, source: HistorySource) -> impl Iterator<Item = &HistoryEntry> {
+        self.entries.values().filter(move |e| e.source == source)
+    }
+    pub fn migrate_apps(&mut self, apps: &[(String, String)]) {
+        for (desktop_id, exec) in apps {
+            let key = history_key(Some(desktop_id), exec);
+            if key.0 != HistorySource::App || key.1 == *exec { continue; }
+            if let Some(old) = self.entries.remove(&(HistorySource::App, exec.clone())) {
+                let entry = self.entry(key.0, key.1);
+                entry.count += old.count;
+                entry.last_used = entry.last_used.max(old.last_used);
+            }
+        }
+    }
+    pub fn prune(&mut self, max_entries: usize, max_age: Option<u64>) -> usize {
+        let before = self.entries.len();
+        if let Some(max_age) = max_age {
+            let cutoff = now().saturating_sub(max_age);
+            self.entries.retain(|_, e| e.last_used >= cutoff);
+        }
+        if max_entries > 0 && self.entries.len() > max_entries {
+            let mut ranked: Vec<(u32, u64, HistoryKey)> = self.entries.iter().map(|(k, e)| (e.count, e.last_used, k.clone())).collect();
+            ranked.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| b.1.cmp(&a.1)));
+            for (_, _, key) in ranked.into_iter().skip(max_entries) {
+                self.entries.remove(&key);
+            }
+        }
+        before - self.entries.len()
+    }
+    pub fn merge(&mut self, other: HistoryStore) -> usize {
+        let added = other.entries.len();
+        for ((source, id), e) in other.entries {
+            let entry = self.entry(source, id);
+            entry.count += e.count;
+            entry.last_used = entry.last_used.max(e.last_used);
+        }
+        added
+    }
+}
+pub fn export_history(path: &str) -> Result<usize, String> {
+    let history = crate::config::load_state().history;
+    let count = history.entries.len();
+    let content = serde_json::to_string_pretty(&history).map_err(|e| e.to_string())?;
+    if path == "-" {
+        println!("{}", content);
+    } else {
+        std::fs::write(path, content).map_err(|e| format!("{}: {}", path, e))?;
+    }
+    Ok(count)
+}
+pub fn import_history(path: &str) -> Result<usize, String> {
+    let content = if path == "-" {
+        std::io::read_to_string(std::io::stdin()).map_err(|e| e.to_string())?
+    } else {
+        std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?
+    };
+    let imported: HistoryStore = serde_json::from_str(&content).map_err(|e| format!("{}: {}", path, e))?;
+    let mut state = crate::config::load_state();
+    let count = state.history.merge(imported);
+    crate::config::save_state(&state);
+    Ok(count)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn key(source: HistorySource, id: &str) -> HistoryKey {
+        (source, id.to_string())
+    }
+    #[test]
+    fn legacy_counts_are_classified() {
+        let manifest = env!("CARGO_MANIFEST_DIR");
+        let json = serde_json::json!({
+            "firefox %u": 3,
+            "xdg-open \"https://example.com\"": 2,
+            "xdg-open \"/etc/hosts\"": 1,
+            "https://rust-lang.org": 4,
+            "RUN:htop": 5,
+            "SESSION:lock": 6,
+            manifest: 7,
+        });
+        let store: HistoryStore = serde_json::from_value(json).unwrap();
+        assert_eq!(store.count(&key(HistorySource::App, "firefox %u")), 3);
+        assert_eq!(store.count(&key(HistorySource::Web, "https://example.com")), 2);
+        assert_eq!(store.count(&key(HistorySource::File, "/etc/hosts")), 1);
+        assert_eq!(store.count(&key(HistorySource::Web, "https://rust-lang.org")), 4);
+        assert_eq!(store.count(&key(HistorySource::Run, "htop")), 5);
+        assert_eq!(store.count(&key(HistorySource::Session, "lock")), 6);
+        assert_eq!(store.count(&key(HistorySource::File, manifest)), 7);
+        assert!(store.iter().all(|e| e.last_used > 0));
+    }
+    #[test]
+    fn legacy_exec_keys_migrate_to_desktop_ids() {
+        let mut store: HistoryStore = serde_json::from_str(r#"{"firefox %u": 3, "gimp %U": 2}"#).unwrap();
+        store.record(key(HistorySource::App, "firefox.desktop"));
+        store.migrate_apps(&[
+            ("firefox.desktop".to_string(), "firefox %u".to_string()),
+            ("gimp.desktop".to_string(), "gimp %U".to_string()),
+        ]);
+        assert_eq!(store.count(&key(HistorySource::App, "firefox.desktop")), 4);
+        assert_eq!(store.count(&key(HistorySource::App, "gimp.desktop")), 2);
+        assert_eq!(store.count(&key(HistorySource::App, "firefox %u")), 0);
+        assert_eq!(store.count(&key(HistorySource::App, "gimp %U")), 0);
+    }
+    #[test]
+    fn prune_drops_old_and_least_used_entries() {
+        let entries = vec![
+            HistoryEntry { source: HistorySource::App, id: "old.desktop".to_string(), count: 50, last_used: 1 },
+            HistoryEntry { source: HistorySource::App, id: "a.desktop".to_string(), count: 5, last_used: now() },
+            HistoryEntry { source: HistorySource::Web, id: "https://b.org".to_string(), count: 3, last_used: now() },
+            HistoryEntry { source: HistorySource::Run, id: "c".to_string(), count: 1, last_used: now() },
+        ];
+        let mut store: HistoryStore = serde_json::from_value(serde_json::to_value(entries).unwrap()).unwrap();
+        assert_eq!(store.prune(2, Some(86400)), 2);
+        assert_eq!(store.count(&key(HistorySource::App, "old.desktop")), 0);
+        assert_eq!(store.count(&key(HistorySource::App, "a.desktop")), 5);
+        assert_eq!(store.count(&key(HistorySource::Web, "https://b.org")), 3);
+        assert_eq!(store.count(&key(HistorySource::Run, "c")), 0);
+    }
+    #[test]
+    fn entries_without_timestamp_age_out() {
+        let mut store: HistoryStore = serde_json::from_str(r#"[{"source":"app","id":"a.desktop","count":1}]"#).unwrap();
+        assert_eq!(store.prune(0, Some(3600)), 0);
+        assert!(store.iter().all(|e| e.last_used > 0));
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+const CATALOGS: &[(&str, &str)] = &[
+    ("de", include_str!("resources/po/de.po")),
+    ("es", include_str!("resources/po/es.po")),
+    ("fr", include_str!("resources/po/fr.po")),
+];
+static TRANSLATIONS: LazyLock<HashMap<String, String>> = LazyLock::new(|| {
+    let lang = current_language();
+    CATALOGS.iter()
+        .find(|(l, _)| Some(*l) == lang.as_deref())
+        .map(|(_, po)| parse_po(po))
+        .unwrap_or_default()
+});
+fn current_language() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+        .filter_map(|v| std::env::var(v).ok())
+        .find(|v| !v.is_empty())
+        .and_then(|v| v.split(['_', '.', '@']).next().map(|l| l.to_lowercase()))
+        .filter(|l| !l.is_empty() && l != "c" && l != "posix")
+}
+fn unquote(s: &str) -> String {
+    let inner = s.trim().trim_start_matches('"').trim_end_matches('"');
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' { out.push(c); continue; }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+fn parse_po(po: &str) -> HashMap<String, String> {
+    let mut map = HashMap::new();
+    let (mut id, mut msg) = (String::new(), String::new());
+    let mut in_msgstr = false;
+    for line in po.lines().map(str::trim) {
+        if let Some(rest) = line.strip_prefix("msgid ") {
+            if !id.is_empty() && !msg.is_empty() {
+                map.insert(std::mem::take(&mut id), std::mem::take(&mut msg));
+            }
+            id = unquote(rest);
+            msg.clear();
+            in_msgstr = false;
+        } else if let Some(rest) = line.strip_prefix("msgstr ") {
+            msg = unquote(rest);
+            in_msgstr = true;
+        } else if line.starts_with('"') {
+            if in_msgstr { msg.push_str(&unquote(line)); } else { id.push_str(&unquote(line)); }
+        }
+    }
+    if !id.is_empty() && !msg.is_empty() {
+        map.insert(id, msg);
+    }
+    map
+}
+pub fn tr(msgid: &str) -> String {
+    TRANSLATIONS.get(msgid).cloned().unwrap_or_else(|| msgid.to_string())
+}
+pub fn trf(msgid: &str, args: &[&str]) -> String {
+    let template = tr(msgid);
+    let mut out = String::with_capacity(template.len());
+    let mut next = 0;
+    let mut rest = template.as_str();
+    while let Some(open) = rest.find('{') {
+        let after = &rest[open + 1..];
+        let Some(close) = after.find('}') else { break; };
+        out.push_str(&rest[..open]);
+        let index = if close == 0 { next += 1; Some(next - 1) } else { after[..close].parse::<usize>().ok() };
+        match index.and_then(|i| args.get(i)) {
+            Some(arg) => out.push_str(arg),
+            None => out.push_str(&rest[open..open + close + 2]),
+        }
+        rest = &after[close + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn parse_po_reads_multiline_and_escaped_entries() {
+        let po = r#"
+msgid ""
+msgstr ""
+"Content-Type: text/plain; charset=UTF-8\n"
+
+msgid "Open"
+msgstr "Öffnen"
+
+msgid "Run '{}'? "
+"Press Enter again"
+msgstr "'{}' ausführen?\t"
+"Erneut \"Enter\" drücken"
+
+msgid "Untranslated"
+msgstr ""
+"#;
+        let map = parse_po(po);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["Open"], "Öffnen");
+        assert_eq!(map["Run '{}'? Press Enter again"], "'{}' ausführen?\tErneut \"Enter\" drücken");
+    }
+    #[test]
+    fn trf_fills_sequential_and_positional_placeholders() {
+        assert_eq!(trf("{} of {}", &["1", "2"]), "1 of 2");
+        assert_eq!(trf("{1} before {0}", &["a", "b"]), "b before a");
+        assert_eq!(trf("{} and {}", &["only"]), "only and {}");
+        assert_eq!(trf("{name} {", &["x"]), "{name} {");
+    }
+}
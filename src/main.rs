@@ -1,5 +1,7 @@
 mod config;
 mod controller;
+mod history;
+mod i18n;
 mod search;
 mod ui;
 mod modules;
@@ -8,9 +10,30 @@ mod wm;
 use gtk4::prelude::*;
 use gtk4::Application;
 fn main() -> glib::ExitCode {
+    let mut args: Vec<String> = std::env::args().collect();
+    for (flag, run) in [("--export-history", history::export_history as fn(&str) -> Result<usize, String>), ("--import-history", history::import_history)] {
+        if let Some(i) = args.iter().position(|a| a == flag) {
+            let Some(path) = args.get(i + 1) else {
+                eprintln!("{} needs a file path (or - for stdout/stdin)", flag);
+                return glib::ExitCode::FAILURE;
+            };
+            return match run(path) {
+                Ok(count) => {
+                    eprintln!("{}: {} entries", flag.trim_start_matches("--"), count);
+                    glib::ExitCode::SUCCESS
+                }
+                Err(err) => {
+                    eprintln!("{}: {}", flag.trim_start_matches("--"), err);
+                    glib::ExitCode::FAILURE
+                }
+            };
+        }
+    }
     let app = Application::builder()
-        .application_id("org.centrum.launcher")
+        .application_id(wm::LAUNCHER_APP_ID)
         .build();
-    app.connect_activate(ui::build_ui);
-    app.run()
+    let incognito = args.iter().any(|a| a == "--incognito");
+    args.retain(|a| a != "--incognito");
+    app.connect_activate(move |app| ui::build_ui(app, incognito));
+    app.run_with_args(&args)
 }
\ No newline at end of file
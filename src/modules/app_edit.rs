@@ -1,20 +1,304 @@
-use crate::config::{load_custom_overrides, save_custom_overrides};
-use crate::search::{get_apps};
+use crate::i18n::tr;
+use crate::config::{find_override, load_custom_overrides, save_custom_overrides, CustomApp};
+use crate::search::{get_apps, AppItem};
 use crate::ui::{EditingMode, LauncherState};
 use crate::modules::app_launcher::update_list_view;
+use crate::modules::path_index::get_path_apps;
 use gtk4::prelude::*;
 use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
+const NON_EDITABLE: &[&str] = &["internal", "file", "web", "clipboard", "command", "run", "system", "calc", "color"];
+#[derive(Clone, Copy)]
+pub enum OverrideFlag {
+    Terminal,
+    NewInstance,
+}
+pub fn override_id(app: &AppItem) -> Option<String> {
+    match app.desktop_id.as_str() {
+        "path" => Some(app.exec.clone()),
+        id if NON_EDITABLE.contains(&id) => None,
+        id => Some(id.to_string()),
+    }
+}
+fn selected_override_id(state: &LauncherState) -> Option<String> {
+    state.filtered_apps.get(state.app_index).and_then(override_id)
+}
+fn env_value(value: &str) -> String {
+    if !value.is_empty() && !value.chars().any(|c| c.is_whitespace() || "\"'\\$`#;&|<>()*?~".contains(c)) {
+        return value.to_string();
+    }
+    glib::shell_quote(value).to_string_lossy().into_owned()
+}
+pub fn start_override_edit(state: &mut LauncherState, mode: EditingMode, edit_label: &gtk4::Label, edit_entry: &gtk4::Entry, popup: &gtk4::Box) -> bool {
+    let Some(id) = selected_override_id(state) else { return false; };
+    let ovr = load_custom_overrides();
+    let current = find_override(&ovr, &id).cloned().unwrap_or_default();
+    let (label, text) = match mode {
+        EditingMode::Keywords => (tr("Edit Keywords (comma separated)"), current.keywords.unwrap_or_default().join(", ")),
+        EditingMode::Args => (tr("Edit Launch Arguments"), current.args.unwrap_or_default()),
+        EditingMode::Env => {
+            let mut vars: Vec<String> = current.env.unwrap_or_default().into_iter().map(|(k, v)| format!("{}={}", k, env_value(&v))).collect();
+            vars.sort();
+            (tr("Edit Environment (KEY=VALUE ...)"), vars.join(" "))
+        }
+        EditingMode::Workdir => (tr("Edit Working Directory"), current.workdir.unwrap_or_default()),
+        _ => return false,
+    };
+    state.editing_mode = mode;
+    state.editing_id = Some(id);
+    edit_label.set_text(&label);
+    if matches!(mode, EditingMode::Keywords) { edit_entry.remove_css_class("mono-text"); } else { edit_entry.add_css_class("mono-text"); }
+    edit_entry.set_text(&text);
+    edit_entry.select_region(0, -1);
+    popup.set_visible(true);
+    edit_entry.grab_focus();
+    true
+}
+pub fn toggle_override_flag(state: &mut LauncherState, flag: OverrideFlag) -> bool {
+    let Some(app) = state.filtered_apps.get(state.app_index).cloned() else { return false; };
+    let Some(id) = override_id(&app) else { return false; };
+    let mut ovr = load_custom_overrides();
+    let ent = ovr.entry(id).or_default();
+    match flag {
+        OverrideFlag::Terminal => ent.terminal = Some(!app.terminal),
+        OverrideFlag::NewInstance => ent.new_instance = Some(!ent.new_instance.unwrap_or(false)),
+    }
+    let new_instance = ent.new_instance.unwrap_or(false);
+    save_custom_overrides(&ovr);
+    let msg = match flag {
+        OverrideFlag::Terminal => if app.terminal { "Runs without terminal" } else { "Runs in terminal" },
+        OverrideFlag::NewInstance => if new_instance { "Always opens a new instance" } else { "Focuses existing window" },
+    };
+    let _ = std::process::Command::new("notify-send").arg(&app.name).arg(tr(msg)).spawn();
+    if matches!(flag, OverrideFlag::Terminal) {
+        let items = state.filtered_apps.iter_mut().chain(state.all_apps.iter_mut()).chain(state.path_apps.iter_mut());
+        for item in items.filter(|a| a.desktop_id == app.desktop_id && a.exec == app.exec) {
+            item.terminal = !app.terminal;
+        }
+    }
+    true
+}
+#[derive(Clone, Copy, PartialEq)]
+pub enum PinAction {
+    Toggle,
+    Up,
+    Down,
+}
+fn listed_ids(state: &LauncherState) -> HashSet<String> {
+    let visible = if state.show_hidden { get_apps(false, &state.theme_config) } else { state.all_apps.clone() };
+    let path_apps = match (state.theme_config.path_index, state.show_hidden) {
+        (false, _) => Vec::new(),
+        (true, true) => get_path_apps(&visible, false),
+        (true, false) => state.path_apps.clone(),
+    };
+    visible.iter().chain(path_apps.iter()).filter_map(override_id).collect()
+}
+pub fn update_pin(state: &mut LauncherState, action: PinAction, refresh_list: bool) -> bool {
+    let Some(app) = state.filtered_apps.get(state.app_index).cloned() else { return false; };
+    let Some(id) = override_id(&app) else { return false; };
+    let mut ovr = load_custom_overrides();
+    let mut pinned: Vec<(String, u32)> = ovr.iter().filter_map(|(k, c)| c.pin.map(|p| (k.clone(), p))).collect();
+    pinned.sort_by_key(|(_, p)| *p);
+    let listed = listed_ids(state);
+    let mut order: Vec<String> = pinned.into_iter().map(|(k, _)| k).filter(|k| listed.contains(k)).collect();
+    let pos = order.iter().position(|k| *k == id);
+    match (action, pos) {
+        (PinAction::Toggle, Some(i)) => { order.remove(i); }
+        (PinAction::Toggle, None) => order.push(id.clone()),
+        (PinAction::Up, Some(i)) if i > 0 => order.swap(i, i - 1),
+        (PinAction::Down, Some(i)) if i + 1 < order.len() => order.swap(i, i + 1),
+        _ => return false,
+    }
+    for ent in ovr.values_mut() {
+        ent.pin = None;
+    }
+    for (i, key) in order.iter().enumerate() {
+        ovr.entry(key.clone()).or_default().pin = Some(i as u32);
+    }
+    save_custom_overrides(&ovr);
+    state.all_apps = get_apps(state.show_hidden, &state.theme_config);
+    if state.theme_config.path_index {
+        state.path_apps = get_path_apps(&state.all_apps, state.show_hidden);
+    }
+    if refresh_list {
+        state.filtered_apps = state.default_list();
+        state.app_index = state.filtered_apps.iter()
+            .position(|a| a.desktop_id == app.desktop_id && a.exec == app.exec)
+            .unwrap_or(0);
+    } else if let Some(item) = state.filtered_apps.get_mut(state.app_index) {
+        item.pin = order.iter().position(|k| *k == id).map(|p| p as u32);
+    }
+    true
+}
+pub fn start_new_entry(state: &mut LauncherState, edit_label: &gtk4::Label, edit_entry: &gtk4::Entry, popup: &gtk4::Box) {
+    state.new_entry = Some(CustomApp::default());
+    state.editing_id = None;
+    show_step(state, EditingMode::NewName, edit_label, edit_entry, popup);
+}
+fn show_step(state: &mut LauncherState, mode: EditingMode, edit_label: &gtk4::Label, edit_entry: &gtk4::Entry, popup: &gtk4::Box) {
+    let (label, mono) = match mode {
+        EditingMode::NewName => ("New Entry: Name", false),
+        EditingMode::NewExec => ("New Entry: Command", true),
+        EditingMode::NewIcon => ("New Entry: Icon", true),
+        EditingMode::NewKeywords => ("New Entry: Keywords (comma separated)", false),
+        EditingMode::NewTerminal => ("New Entry: Run in terminal? (y/n)", false),
+        EditingMode::NewExport => ("New Entry: Export .desktop file? (y/n)", false),
+        _ => return,
+    };
+    state.editing_mode = mode;
+    edit_label.set_text(&tr(label));
+    if mono { edit_entry.add_css_class("mono-text"); } else { edit_entry.remove_css_class("mono-text"); }
+    edit_entry.set_text(if matches!(mode, EditingMode::NewTerminal | EditingMode::NewExport) { "n" } else { "" });
+    edit_entry.select_region(0, -1);
+    popup.set_visible(true);
+    edit_entry.grab_focus();
+}
+fn slugify(name: &str) -> String {
+    let slug: String = name.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect();
+    slug.split('-').filter(|p| !p.is_empty()).collect::<Vec<_>>().join("-")
+}
+fn desktop_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for (i, c) in s.chars().enumerate() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            ' ' if i == 0 => out.push_str("\\s"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+fn desktop_exec_arg(arg: &str) -> String {
+    let arg = arg.replace('%', "%%");
+    if !arg.is_empty() && !arg.chars().any(|c| c.is_whitespace() || "\"'\\><~|&;$*?#()`".contains(c)) {
+        return arg;
+    }
+    let mut out = String::from("\"");
+    for c in arg.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') { out.push('\\'); }
+        out.push(c);
+    }
+    out.push('"');
+    out
+}
+fn desktop_exec(cmd: &str) -> String {
+    let plain = !cmd.chars().any(|c| "|&;<>()$`*?~\n".contains(c));
+    let args: Vec<String> = glib::shell_parse_argv(cmd).ok().filter(|_| plain)
+        .map(|argv| argv.iter().map(|a| a.to_string_lossy().into_owned()).collect())
+        .unwrap_or_else(|| vec!["sh".to_string(), "-c".to_string(), cmd.to_string()]);
+    desktop_string(&args.iter().map(|a| desktop_exec_arg(a)).collect::<Vec<_>>().join(" "))
+}
+fn export_desktop_file(slug: &str, entry: &CustomApp) -> Option<String> {
+    let dir = glib::user_data_dir().join("applications");
+    std::fs::create_dir_all(&dir).ok()?;
+    let mut desktop_id = format!("centrum-{}.desktop", slug);
+    let mut n = 2;
+    while dir.join(&desktop_id).exists() {
+        desktop_id = format!("centrum-{}-{}.desktop", slug, n);
+        n += 1;
+    }
+    let mut content = format!(
+        "[Desktop Entry]\nType=Application\nName={}\nExec={}\nTerminal={}\n",
+        desktop_string(entry.name.as_deref().unwrap_or(slug)),
+        desktop_exec(entry.exec.as_deref().unwrap_or_default()),
+        entry.terminal.unwrap_or(false),
+    );
+    if let Some(icon) = &entry.system_icon {
+        content.push_str(&format!("Icon={}\n", desktop_string(icon)));
+    }
+    if let Some(keywords) = entry.keywords.as_ref().filter(|k| !k.is_empty()) {
+        let keywords: Vec<String> = keywords.iter().map(|k| desktop_string(k).replace(';', "\\;")).collect();
+        content.push_str(&format!("Keywords={};\n", keywords.join(";")));
+    }
+    std::fs::write(dir.join(&desktop_id), content).ok()?;
+    Some(desktop_id)
+}
+fn finish_new_entry(state: &mut LauncherState, export: bool) {
+    let Some(mut entry) = state.new_entry.take() else { return; };
+    let slug = slugify(entry.name.as_deref().unwrap_or_default());
+    if slug.is_empty() { return; }
+    let mut ovr = load_custom_overrides();
+    let exported = if export { export_desktop_file(&slug, &entry) } else { None };
+    if let Some(desktop_id) = exported {
+        if entry.icon.is_some() {
+            ovr.insert(desktop_id, CustomApp { icon: entry.icon.take(), ..Default::default() });
+        }
+    } else {
+        let mut id = format!("custom-{}", slug);
+        let mut n = 2;
+        while ovr.contains_key(&id) {
+            id = format!("custom-{}-{}", slug, n);
+            n += 1;
+        }
+        ovr.insert(id, entry);
+    }
+    save_custom_overrides(&ovr);
+}
+fn handle_new_entry_step(
+    state: &mut LauncherState,
+    val: String,
+    edit_label: &gtk4::Label,
+    edit_entry: &gtk4::Entry,
+    popup: &gtk4::Box,
+) -> bool {
+    let yes = val.trim().to_lowercase().starts_with('y');
+    let icon_mode = state.theme_config.icon_mode.clone();
+    let Some(entry) = state.new_entry.as_mut() else { return true; };
+    let next = match state.editing_mode {
+        EditingMode::NewName => {
+            if val.trim().is_empty() { return false; }
+            entry.name = Some(val.trim().to_string());
+            EditingMode::NewExec
+        }
+        EditingMode::NewExec => {
+            if val.trim().is_empty() { return false; }
+            entry.exec = Some(val.trim().to_string());
+            EditingMode::NewIcon
+        }
+        EditingMode::NewIcon => {
+            let icon = Some(val.trim().to_string()).filter(|v| !v.is_empty());
+            if icon_mode == "system" { entry.system_icon = icon; } else { entry.icon = icon; }
+            EditingMode::NewKeywords
+        }
+        EditingMode::NewKeywords => {
+            let keywords: Vec<String> = val.split(',').map(|k| k.trim().to_string()).filter(|k| !k.is_empty()).collect();
+            entry.keywords = Some(keywords).filter(|k| !k.is_empty());
+            EditingMode::NewTerminal
+        }
+        EditingMode::NewTerminal => {
+            entry.terminal = Some(yes);
+            EditingMode::NewExport
+        }
+        _ => {
+            finish_new_entry(state, yes);
+            return true;
+        }
+    };
+    show_step(state, next, edit_label, edit_entry, popup);
+    false
+}
 pub fn handle_app_edit(
     state: &Rc<RefCell<LauncherState>>,
     window: &gtk4::ApplicationWindow,
     container: &gtk4::Box,
+    edit_label: &gtk4::Label,
     edit_entry: &gtk4::Entry,
     popup: &gtk4::Box,
-) {
+) -> bool {
     let mut sh = state.borrow_mut();
     let val = edit_entry.text().to_string();
-    if let Some(id) = sh.editing_id.clone() {
+    if sh.new_entry.is_some() {
+        if !handle_new_entry_step(&mut sh, val, edit_label, edit_entry, popup) {
+            return false;
+        }
+    } else if let Some(id) = sh.editing_id.clone() {
         let mut ovr = load_custom_overrides();
         let ent = ovr.entry(id).or_default();
         match sh.editing_mode { 
@@ -28,21 +312,59 @@ pub fn handle_app_edit(
                     ent.icon = field_val;
                 }
             },
+            EditingMode::Keywords => {
+                let keywords: Vec<String> = val.split(',').map(|k| k.trim().to_string()).filter(|k| !k.is_empty()).collect();
+                ent.keywords = Some(keywords).filter(|k| !k.is_empty());
+            },
+            EditingMode::Args => ent.args = Some(val.trim().to_string()).filter(|v| !v.is_empty()),
+            EditingMode::Env => {
+                let parsed = if val.trim().is_empty() { Ok(Vec::new()) } else { glib::shell_parse_argv(val.trim()) };
+                if let Ok(args) = parsed {
+                    let env: HashMap<String, String> = args.iter()
+                        .filter_map(|pair| pair.to_str()?.split_once('='))
+                        .filter(|(k, _)| !k.is_empty())
+                        .map(|(k, v)| (k.to_string(), v.to_string()))
+                        .collect();
+                    ent.env = Some(env).filter(|e| !e.is_empty());
+                }
+            },
+            EditingMode::Workdir => ent.workdir = Some(val.trim().to_string()).filter(|v| !v.is_empty()),
             _ => {} 
         }
         save_custom_overrides(&ovr);
-        sh.all_apps = get_apps(sh.show_hidden); 
-        
-        let history = sh.history.clone();
-        sh.all_apps.sort_by(|a, b| {
-            history.get(&b.exec).unwrap_or(&0).cmp(history.get(&a.exec).unwrap_or(&0))
-        });
-        
-        sh.filtered_apps = sh.all_apps.clone();
-        sh.editing_mode = EditingMode::None; 
-        sh.editing_id = None;
-        let config = sh.theme_config.clone();
-        update_list_view(container, &sh.filtered_apps, sh.app_index, window, state, &config);
-        popup.set_visible(false);
+    } else {
+        return false;
+    }
+    sh.all_apps = get_apps(sh.show_hidden, &sh.theme_config); 
+    if sh.theme_config.path_index {
+        sh.path_apps = get_path_apps(&sh.all_apps, sh.show_hidden);
+    }
+    sh.filtered_apps = sh.default_list();
+    sh.editing_mode = EditingMode::None; 
+    sh.editing_id = None;
+    let config = sh.theme_config.clone();
+    update_list_view(container, &sh.filtered_apps, sh.app_index, window, state, &config);
+    popup.set_visible(false);
+    true
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn slugify_collapses_separators() {
+        assert_eq!(slugify("My  Cool App!"), "my-cool-app");
+        assert_eq!(slugify("--Über_Tool 2--"), "über-tool-2");
+        assert_eq!(slugify("!!!"), "");
+    }
+    #[test]
+    fn desktop_exec_quotes_arguments() {
+        assert_eq!(desktop_exec("firefox --new-window"), "firefox --new-window");
+        assert_eq!(desktop_exec("mpv '/home/me/My Videos'"), "mpv \"/home/me/My Videos\"");
+        assert_eq!(desktop_exec("printf 100%"), "printf 100%%");
+        assert_eq!(desktop_exec("echo \"$HOME\""), "sh -c \"echo \\\\\"\\\\$HOME\\\\\"\"");
+    }
+    #[test]
+    fn desktop_exec_wraps_shell_syntax() {
+        assert_eq!(desktop_exec("ls | wc -l"), "sh -c \"ls | wc -l\"");
     }
 }
@@ -1,5 +1,8 @@
 use crate::ui::{LauncherState, create_hotkeys_window};
 use crate::controller::launch_app;
+use crate::i18n::trf;
+use crate::modules::categories::is_section;
+use crate::modules::power_menu::activate_power_option;
 use crate::search::AppItem;
 use gtk4::prelude::*;
 use gtk4::{Align, ApplicationWindow, Box, Image, Label, Orientation, ScrolledWindow};
@@ -23,11 +26,7 @@ pub fn create_app_list() -> (ScrolledWindow, Box) {
 pub fn initialize_list_view(container: &Box, window: &ApplicationWindow, state: &Rc<RefCell<LauncherState>>) {
     let (apps, config) = {
         let mut s = state.borrow_mut();
-        s.filtered_apps = s.all_apps.clone();
-        let history = s.history.clone();
-        s.filtered_apps.sort_by(|a, b| {
-            history.get(&b.exec).unwrap_or(&0).cmp(history.get(&a.exec).unwrap_or(&0))
-        });
+        s.filtered_apps = s.default_list();
         (s.filtered_apps.clone(), s.theme_config.clone())
     };
     update_list_view(container, &apps, 0, window, state, &config);
@@ -51,6 +50,12 @@ pub fn update_list_view(
     let max_chars = if icon_pos == "fixed" { -1 } else { ((win_width - 150) / 12).max(10) };
 
     for (i, app) in apps.iter().take(100).enumerate() {
+        if is_section(app) {
+            let header = Label::builder().label(&app.name).xalign(align_val).margin_top(12).margin_start(28).margin_end(28).build();
+            header.add_css_class("section-header");
+            container.append(&header);
+            continue;
+        }
         let ib = Box::builder()
             .halign(Align::Fill)
             .valign(Align::Center)
@@ -60,8 +65,9 @@ pub fn update_list_view(
             .margin_end(20)
             .build();
         ib.add_css_class("app-pill");
+        if app.pin.is_some() { ib.add_css_class("pinned"); }
         ib.set_cursor_from_name(Some("pointer"));
-        let (exec, term, win, st, did, fol, term_cmd) = (
+        let (exec, term, win, st, did, fol, term_cmd, list, rules) = (
             app.exec.clone(),
             app.terminal,
             window.clone(),
@@ -69,15 +75,35 @@ pub fn update_list_view(
             app.desktop_id.clone(),
             config.focus_on_launch,
             config.terminal.clone(),
+            container.clone(),
+            config.browser_rules.clone(),
         );
         let gest = gtk4::GestureClick::new();
         gest.connect_pressed(move |_, _, _, _| {
+            if exec.is_empty() { return; }
             if exec == "SHOW_HOTKEYS" {
                 let app_ref = win.application().expect("App error");
                 create_hotkeys_window(&app_ref, &st);
                 return;
             }
-            launch_app(&exec, term, &mut st.borrow_mut().history, Some(&did), fol, &term_cmd);
+            if let Some(idx) = exec.strip_prefix("POWER:").and_then(|i| i.parse().ok()) {
+                activate_power_option(idx, &st, &win, true);
+                return;
+            }
+            if let Some(kw) = exec.strip_prefix("ENGINE:") {
+                let entry = gtk4::prelude::GtkWindowExt::focus(&win)
+                    .and_then(|f| f.ancestor(gtk4::Entry::static_type()))
+                    .and_then(|w| w.downcast::<gtk4::Entry>().ok());
+                if let Some(entry) = entry {
+                    entry.set_text(&format!("?{} ", kw));
+                    entry.set_position(-1);
+                }
+                return;
+            }
+            if request_confirmation(&list, &win, &st, i) {
+                return;
+            }
+            launch_app(&exec, term, &mut st.borrow_mut().history, Some(&did), fol, &term_cmd, &rules);
             win.close();
         });
         ib.add_controller(gest);
@@ -135,8 +161,53 @@ pub fn update_list_view(
             nm_lbl.set_hexpand(false);
             nm_lbl.set_halign(if align_val < 0.4 { Align::Start } else if align_val > 0.6 { Align::End } else { Align::Center });
         }
-        cb.append(&nm_lbl);
+        let badge = app.source.badge().map(|b| {
+            let lbl = Label::builder().label(b).valign(Align::Center).build();
+            lbl.add_css_class("source-badge");
+            lbl.set_margin_start(10);
+            lbl
+        });
+        let subtitle = Some(&app.subtitle).filter(|s| !s.is_empty())
+            .or_else(|| Some(&app.generic_name).filter(|g| config.show_generic_name && !g.is_empty() && **g != app.name));
+        if let Some(sub) = subtitle {
+            let text_box = Box::builder().orientation(Orientation::Vertical).valign(Align::Center).build();
+            text_box.set_hexpand(nm_lbl.hexpands());
+            text_box.set_halign(nm_lbl.halign());
+            let sub_lbl = Label::builder().label(sub).xalign(align_val).justify(justify).build();
+            sub_lbl.add_css_class("app-subtitle");
+            text_box.append(&nm_lbl);
+            text_box.append(&sub_lbl);
+            cb.append(&text_box);
+        } else {
+            cb.append(&nm_lbl);
+        }
+        if let Some(b) = &badge {
+            cb.append(b);
+        }
         ib.append(&cb);
         container.append(&ib);
     }
 }
+pub fn request_confirmation(container: &Box, window: &ApplicationWindow, state: &Rc<RefCell<LauncherState>>, idx: usize) -> bool {
+    let mut sh = state.borrow_mut();
+    let Some(app) = sh.filtered_apps.get(idx).cloned() else { return false; };
+    if !app.confirm || sh.pending_confirm.as_ref().map(|(i, _)| *i == idx).unwrap_or(false) {
+        return false;
+    }
+    cancel_confirmation(&mut sh);
+    sh.filtered_apps[idx].name = trf("Run '{}'? Press Enter again", &[&app.name]);
+    sh.pending_confirm = Some((idx, app.name));
+    sh.app_index = idx;
+    let (apps, config) = (sh.filtered_apps.clone(), sh.theme_config.clone());
+    drop(sh);
+    update_list_view(container, &apps, idx, window, state, &config);
+    true
+}
+pub fn cancel_confirmation(state: &mut LauncherState) -> bool {
+    state.pending_clear = false;
+    let Some((idx, name)) = state.pending_confirm.take() else { return false; };
+    if let Some(app) = state.filtered_apps.get_mut(idx) {
+        app.name = name;
+    }
+    true
+}
@@ -0,0 +1,235 @@
+use crate::config::get_cache_dir;
+use crate::i18n::trf;
+use crate::search::AppItem;
+use crate::utils::find_in_path;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::rc::Rc;
+const CHROMIUM_DIRS: &[(&str, &str)] = &[
+    ("google-chrome", "Chrome"),
+    ("chromium", "Chromium"),
+    ("BraveSoftware/Brave-Browser", "Brave"),
+    ("vivaldi", "Vivaldi"),
+    ("microsoft-edge", "Edge"),
+];
+const FIREFOX_DIRS: &[&str] = &[
+    ".mozilla/firefox",
+    ".var/app/org.mozilla.firefox/.mozilla/firefox",
+    "snap/firefox/common/.mozilla/firefox",
+    ".librewolf",
+];
+#[derive(Clone, Debug)]
+pub struct BrowserEntry {
+    pub title: String,
+    pub url: String,
+    pub visits: u32,
+    pub bookmark: bool,
+    pub browser: String,
+}
+thread_local! {
+    static BROWSER_DATA: RefCell<Option<Rc<Vec<BrowserEntry>>>> = const { RefCell::new(None) };
+    static LOADING: RefCell<bool> = const { RefCell::new(false) };
+}
+fn home() -> PathBuf {
+    PathBuf::from(std::env::var("HOME").unwrap_or_default())
+}
+fn profile_dirs(root: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(root)
+        .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()).filter(|p| p.is_dir()).collect())
+        .unwrap_or_default()
+}
+fn with_db_copy<T>(src: &Path, f: impl FnOnce(&Path) -> T) -> Option<T> {
+    let dir = glib::mkdtemp(glib::tmp_dir().join("centrum-browser-XXXXXX"))?;
+    let dest = dir.join("db.sqlite");
+    let wal = PathBuf::from(format!("{}-wal", src.display()));
+    let copied = std::fs::copy(src, &dest).is_ok()
+        && (!wal.exists() || std::fs::copy(&wal, format!("{}-wal", dest.display())).is_ok());
+    let result = copied.then(|| f(&dest));
+    for suffix in ["", "-wal", "-shm", "-journal"] {
+        let _ = std::fs::remove_file(format!("{}{}", dest.display(), suffix));
+    }
+    let _ = std::fs::remove_dir(&dir);
+    result
+}
+fn query_db(db: &Path, sql: &str) -> Vec<Vec<String>> {
+    let output = Command::new("sqlite3")
+        .args(["-separator", "\u{1f}", "-newline", "\u{1e}"])
+        .arg(db)
+        .arg(sql)
+        .output();
+    match output {
+        Ok(o) if o.status.success() => String::from_utf8_lossy(&o.stdout)
+            .split('\u{1e}')
+            .filter(|row| !row.is_empty())
+            .map(|row| row.split('\u{1f}').map(str::to_string).collect())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+fn row_entry(row: &[String], bookmark: bool, browser: &str) -> Option<BrowserEntry> {
+    let url = row.get(1)?.clone();
+    if !url.starts_with("http") { return None; }
+    Some(BrowserEntry {
+        title: row.first().cloned().unwrap_or_default(),
+        url,
+        visits: row.get(2).and_then(|v| v.parse().ok()).unwrap_or(0),
+        bookmark,
+        browser: browser.to_string(),
+    })
+}
+fn read_firefox(entries: &mut Vec<BrowserEntry>) {
+    for root in FIREFOX_DIRS {
+        for profile in profile_dirs(&home().join(root)) {
+            let places = profile.join("places.sqlite");
+            if !places.exists() { continue; }
+            let Some((bookmarks, history)) = with_db_copy(&places, |db| (
+                query_db(db, "SELECT b.title, p.url, p.visit_count FROM moz_bookmarks b JOIN moz_places p ON b.fk = p.id WHERE b.type = 1;"),
+                query_db(db, "SELECT title, url, visit_count FROM moz_places WHERE visit_count > 0 AND hidden = 0 ORDER BY frecency DESC LIMIT 500;"),
+            )) else { continue; };
+            entries.extend(bookmarks.iter().filter_map(|r| row_entry(r, true, "Firefox")));
+            entries.extend(history.iter().filter_map(|r| row_entry(r, false, "Firefox")));
+        }
+    }
+}
+fn collect_chromium_bookmarks(node: &serde_json::Value, browser: &str, entries: &mut Vec<BrowserEntry>) {
+    if node.get("type").and_then(|t| t.as_str()) == Some("url") {
+        if let Some(url) = node.get("url").and_then(|u| u.as_str()).filter(|u| u.starts_with("http")) {
+            entries.push(BrowserEntry {
+                title: node.get("name").and_then(|n| n.as_str()).unwrap_or_default().to_string(),
+                url: url.to_string(),
+                visits: 0,
+                bookmark: true,
+                browser: browser.to_string(),
+            });
+        }
+    }
+    let children = node.get("children").and_then(|c| c.as_array()).into_iter().flatten();
+    let roots = node.get("roots").and_then(|r| r.as_object()).into_iter().flat_map(|r| r.values());
+    for child in children.chain(roots) {
+        collect_chromium_bookmarks(child, browser, entries);
+    }
+}
+fn read_chromium(entries: &mut Vec<BrowserEntry>, with_history: bool) {
+    let config = glib::user_config_dir();
+    for (dir, browser) in CHROMIUM_DIRS {
+        for profile in profile_dirs(&config.join(dir)) {
+            if let Some(json) = std::fs::read_to_string(profile.join("Bookmarks")).ok().and_then(|c| serde_json::from_str(&c).ok()) {
+                collect_chromium_bookmarks(&json, browser, entries);
+            }
+            let history = profile.join("History");
+            if !with_history || !history.exists() { continue; }
+            let Some(rows) = with_db_copy(&history, |db| query_db(db, "SELECT title, url, visit_count FROM urls WHERE hidden = 0 ORDER BY visit_count DESC LIMIT 500;")) else { continue; };
+            entries.extend(rows.iter().filter_map(|r| row_entry(r, false, browser)));
+        }
+    }
+}
+pub fn read_browser_data() -> Vec<BrowserEntry> {
+    let mut entries = Vec::new();
+    let _ = std::fs::remove_dir_all(get_cache_dir().join("browser"));
+    let sqlite = find_in_path("sqlite3");
+    if sqlite {
+        read_firefox(&mut entries);
+    }
+    read_chromium(&mut entries, sqlite);
+    let mut merged: HashMap<String, BrowserEntry> = HashMap::new();
+    for e in entries {
+        match merged.get_mut(&e.url) {
+            Some(existing) => {
+                existing.visits = existing.visits.max(e.visits);
+                existing.bookmark |= e.bookmark;
+                if existing.title.is_empty() { existing.title = e.title; }
+            }
+            None => { merged.insert(e.url.clone(), e); }
+        }
+    }
+    let mut entries: Vec<BrowserEntry> = merged.into_values().collect();
+    entries.sort_by(|a, b| b.bookmark.cmp(&a.bookmark).then_with(|| b.visits.cmp(&a.visits)));
+    entries
+}
+pub fn browser_entries(done: impl FnOnce() + 'static) -> Option<Rc<Vec<BrowserEntry>>> {
+    if let Some(entries) = BROWSER_DATA.with(|d| d.borrow().clone()) {
+        return Some(entries);
+    }
+    if LOADING.with(|l| l.replace(true)) {
+        return None;
+    }
+    glib::spawn_future_local(async move {
+        let entries = gio::spawn_blocking(read_browser_data).await.unwrap_or_default();
+        BROWSER_DATA.with(|d| *d.borrow_mut() = Some(Rc::new(entries)));
+        LOADING.with(|l| *l.borrow_mut() = false);
+        done();
+    });
+    None
+}
+pub fn score_browser(matcher: &SkimMatcherV2, entry: &BrowserEntry, query: &str) -> Option<i64> {
+    let title = matcher.fuzzy_match(&entry.title, query);
+    let url = matcher.fuzzy_match(&entry.url, query).map(|s| s * 8 / 10);
+    let score = title.max(url)?;
+    Some(score + if entry.bookmark { 20 } else { 0 } + (entry.visits.min(100) as i64) / 5)
+}
+pub fn browser_item(entry: &BrowserEntry) -> AppItem {
+    let display = entry.url.trim_start_matches("https://").trim_start_matches("http://").trim_end_matches('/');
+    let kind = if entry.bookmark { "Bookmark" } else { "History" };
+    AppItem {
+        name: if entry.title.is_empty() { display.to_string() } else { entry.title.clone() },
+        exec: format!("xdg-open {}", entry.url),
+        terminal: false,
+        icon: if entry.bookmark { "\u{f02e}" } else { "\u{f1da}" }.to_string(),
+        desktop_id: "web".to_string(),
+        subtitle: trf("{0} · {1} {2}", &[display, &entry.browser, &crate::i18n::tr(kind)]),
+        ..Default::default()
+    }
+}
+pub fn check_browser(query: &str, entries: &[BrowserEntry], matcher: &SkimMatcherV2, limit: usize) -> Vec<(i64, AppItem)> {
+    let query = query.trim();
+    let mut matches: Vec<(i64, &BrowserEntry)> = if query.is_empty() {
+        entries.iter().map(|e| (0, e)).collect()
+    } else {
+        entries.iter().filter_map(|e| score_browser(matcher, e, query).map(|s| (s, e))).collect()
+    };
+    matches.sort_by(|a, b| b.0.cmp(&a.0));
+    matches.into_iter().take(limit).map(|(s, e)| (s, browser_item(e))).collect()
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn row(fields: &[&str]) -> Vec<String> {
+        fields.iter().map(|f| f.to_string()).collect()
+    }
+    #[test]
+    fn chromium_bookmarks_are_collected_recursively() {
+        let json = serde_json::json!({
+            "roots": {
+                "bookmark_bar": {
+                    "type": "folder",
+                    "children": [
+                        { "type": "url", "name": "Rust", "url": "https://www.rust-lang.org/" },
+                        { "type": "folder", "name": "Docs", "children": [
+                            { "type": "url", "name": "GTK", "url": "https://docs.gtk.org/gtk4/" },
+                            { "type": "url", "name": "Local", "url": "file:///home/me/notes.html" }
+                        ] }
+                    ]
+                },
+                "other": { "type": "folder", "children": [] }
+            }
+        });
+        let mut entries = Vec::new();
+        collect_chromium_bookmarks(&json, "Chromium", &mut entries);
+        let urls: Vec<&str> = entries.iter().map(|e| e.url.as_str()).collect();
+        assert_eq!(urls, ["https://www.rust-lang.org/", "https://docs.gtk.org/gtk4/"]);
+        assert!(entries.iter().all(|e| e.bookmark && e.visits == 0 && e.browser == "Chromium"));
+        assert_eq!(entries[1].title, "GTK");
+    }
+    #[test]
+    fn history_rows_become_entries() {
+        let entry = row_entry(&row(&["Rust", "https://www.rust-lang.org/", "42"]), false, "Firefox").unwrap();
+        assert_eq!((entry.title.as_str(), entry.url.as_str(), entry.visits, entry.bookmark), ("Rust", "https://www.rust-lang.org/", 42, false));
+        assert_eq!(row_entry(&row(&["", "https://example.com", "n/a"]), true, "Firefox").map(|e| e.visits), Some(0));
+        assert!(row_entry(&row(&["Settings", "about:preferences", "3"]), false, "Firefox").is_none());
+        assert!(row_entry(&row(&["Only title"]), false, "Firefox").is_none());
+    }
+}
@@ -0,0 +1,102 @@
+use crate::i18n::tr;
+use crate::search::{score_item, AppItem};
+use fuzzy_matcher::skim::SkimMatcherV2;
+use crate::history::HistoryStore;
+const CATEGORIES: &[(&str, &str, &str)] = &[
+    ("dev", "development", "Development"),
+    ("graphics", "graphics", "Graphics"),
+    ("games", "game", "Games"),
+    ("net", "network", "Internet"),
+    ("office", "office", "Office"),
+    ("media", "audiovideo", "Multimedia"),
+    ("sys", "system", "System"),
+    ("util", "utility", "Utilities"),
+    ("settings", "settings", "Settings"),
+    ("edu", "education", "Education"),
+    ("sci", "science", "Science"),
+];
+pub fn is_section(item: &AppItem) -> bool {
+    item.desktop_id == "section"
+}
+pub fn next_selectable(apps: &[AppItem], from: usize, forward: bool) -> Option<usize> {
+    if forward {
+        (from + 1..apps.len()).find(|&i| !is_section(&apps[i]))
+    } else {
+        (0..from).rev().find(|&i| !is_section(&apps[i]))
+    }
+}
+pub fn first_selectable(apps: &[AppItem]) -> usize {
+    apps.iter().position(|a| !is_section(a)).unwrap_or(0)
+}
+fn section(label: &str) -> AppItem {
+    AppItem {
+        name: tr(label),
+        desktop_id: "section".to_string(),
+        ..Default::default()
+    }
+}
+pub fn check_categories(query: &str, apps: &[AppItem], history: &HistoryStore, matcher: &SkimMatcherV2) -> Vec<AppItem> {
+    let mut parts = query.trim_start().splitn(2, char::is_whitespace);
+    let cat_query = parts.next().unwrap_or_default().to_lowercase();
+    let filter = parts.next().unwrap_or_default().trim();
+    let selected: Vec<&(&str, &str, &str)> = CATEGORIES.iter()
+        .filter(|(alias, cat, label)| {
+            cat_query.is_empty() || alias.starts_with(&cat_query) || cat.starts_with(&cat_query) || label.to_lowercase().starts_with(&cat_query)
+        })
+        .collect();
+    let mut results = Vec::new();
+    for (_, cat, label) in &selected {
+        let mut members: Vec<(i64, &AppItem)> = apps.iter()
+            .filter(|a| a.categories.iter().any(|c| c == cat))
+            .filter_map(|a| if filter.is_empty() { Some((0, a)) } else { score_item(matcher, a, filter).map(|sc| (sc, a)) })
+            .collect();
+        if members.is_empty() { continue; }
+        members.sort_by(|(s1, a), (s2, b)| {
+            s2.cmp(s1)
+                .then_with(|| history.count_item(b).cmp(&history.count_item(a)))
+                .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+        });
+        results.push(section(label));
+        results.extend(members.into_iter().map(|(_, a)| a.clone()));
+    }
+    if cat_query.is_empty() && filter.is_empty() {
+        let mut others: Vec<&AppItem> = apps.iter()
+            .filter(|a| !a.categories.iter().any(|c| CATEGORIES.iter().any(|(_, cat, _)| c == cat)))
+            .collect();
+        if !others.is_empty() {
+            others.sort_by_key(|a| a.name.to_lowercase());
+            results.push(section("Other"));
+            results.extend(others.into_iter().cloned());
+        }
+    }
+    results
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn app(name: &str, categories: &[&str]) -> AppItem {
+        AppItem { name: name.to_string(), exec: name.to_lowercase(), categories: categories.iter().map(|c| c.to_string()).collect(), ..Default::default() }
+    }
+    fn names(items: &[AppItem]) -> Vec<String> {
+        items.iter().map(|a| a.name.clone()).collect()
+    }
+    fn apps() -> Vec<AppItem> {
+        vec![app("Gimp", &["graphics"]), app("Code", &["development", "utility"]), app("Inkscape", &["graphics"]), app("Notes", &[])]
+    }
+    #[test]
+    fn empty_query_lists_every_category_and_other() {
+        let results = check_categories("", &apps(), &HistoryStore::default(), &SkimMatcherV2::default());
+        assert_eq!(names(&results), [tr("Development"), "Code".into(), tr("Graphics"), "Gimp".into(), "Inkscape".into(), tr("Utilities"), "Code".into(), tr("Other"), "Notes".into()]);
+        assert_eq!(first_selectable(&results), 1);
+        assert_eq!(next_selectable(&results, 1, true), Some(3));
+    }
+    #[test]
+    fn category_prefix_and_filter_narrow_results() {
+        let matcher = SkimMatcherV2::default();
+        let results = check_categories("graph", &apps(), &HistoryStore::default(), &matcher);
+        assert_eq!(names(&results), [tr("Graphics"), "Gimp".into(), "Inkscape".into()]);
+        let results = check_categories("graphics ink", &apps(), &HistoryStore::default(), &matcher);
+        assert_eq!(names(&results), [tr("Graphics"), "Inkscape".into()]);
+        assert!(check_categories("games", &apps(), &HistoryStore::default(), &matcher).is_empty());
+    }
+}
@@ -269,7 +269,7 @@ pub fn setup_color_picker_logic(state: &Rc<RefCell<LauncherState>>) {
             let hex = format!("#{:02X}{:02X}{:02X}{:02X}", (r*255.0) as u8, (g*255.0) as u8, (b*255.0) as u8, (sh.current_alpha*255.0) as u8);
             let mut hist = sh.history.clone();
             let term_cmd = sh.theme_config.terminal.clone();
-            launch_app(&format!("COPY:{}", hex), false, &mut hist, Some("color"), sh.theme_config.focus_on_launch, &term_cmd);
+            launch_app(&format!("COPY:{}", hex), false, &mut hist, Some("color"), sh.theme_config.focus_on_launch, &term_cmd, &[]);
         });
     }
     if let Some(rb) = &state.borrow().rgb_copy_btn {
@@ -281,7 +281,7 @@ pub fn setup_color_picker_logic(state: &Rc<RefCell<LauncherState>>) {
             let rgb_str = format!("rgba({},{},{},{:.2})", (r*255.0) as u8, (g*255.0) as u8, (b*255.0) as u8, sh.current_alpha);
             let mut hist = sh.history.clone();
             let term_cmd = sh.theme_config.terminal.clone();
-            launch_app(&format!("COPY:{}", rgb_str), false, &mut hist, Some("color"), sh.theme_config.focus_on_launch, &term_cmd);
+            launch_app(&format!("COPY:{}", rgb_str), false, &mut hist, Some("color"), sh.theme_config.focus_on_launch, &term_cmd, &[]);
         });
     }
 }
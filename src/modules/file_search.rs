@@ -48,6 +48,7 @@ pub fn check_files(query: &str, _matcher: &SkimMatcherV2, _existing_items: &[App
             terminal: false,
             desktop_id: "file".to_string(),
             system_icon: None,
+            ..Default::default()
         }];
     }
 
@@ -90,6 +91,7 @@ pub fn check_files(query: &str, _matcher: &SkimMatcherV2, _existing_items: &[App
                 terminal: false,
                 desktop_id: "file".to_string(),
                 system_icon: None,
+                ..Default::default()
             };
             (item, is_exact, starts_with, is_hidden, depth, path_str)
         })
@@ -121,6 +123,7 @@ fn list_dir(path: &Path) -> Vec<AppItem> {
                 terminal: false,
                 desktop_id: "file".to_string(),
                 system_icon: None,
+                ..Default::default()
             }
         })
         .collect();
@@ -0,0 +1,105 @@
+use crate::config::PowerOption;
+use crate::history::{history_key, now, parse_age, HistoryEntry, HistoryKey, HistorySource, HistoryStore};
+use crate::i18n::{tr, trf};
+use crate::search::AppItem;
+use crate::utils::find_in_path;
+use gio::prelude::*;
+use std::collections::HashMap;
+#[derive(Default)]
+pub struct HistoryFilter {
+    pub sources: Vec<HistorySource>,
+    pub older_than: Option<u64>,
+    pub newer_than: Option<u64>,
+    pub text: String,
+}
+impl HistoryFilter {
+    pub fn parse(query: &str) -> Self {
+        let mut filter = HistoryFilter::default();
+        let mut words = Vec::new();
+        for token in query.split_whitespace() {
+            if let Some(source) = token.strip_suffix(':').and_then(HistorySource::parse) {
+                filter.sources.push(source);
+            } else if let Some(age) = token.strip_prefix('>').and_then(parse_age) {
+                filter.older_than = Some(age);
+            } else if let Some(age) = token.strip_prefix('<').and_then(parse_age) {
+                filter.newer_than = Some(age);
+            } else {
+                words.push(token.to_lowercase());
+            }
+        }
+        filter.text = words.join(" ");
+        filter
+    }
+    fn matches(&self, entry: &HistoryEntry, name: &str, now: u64) -> bool {
+        if !self.sources.is_empty() && !self.sources.contains(&entry.source) { return false; }
+        let age = now.saturating_sub(entry.last_used);
+        if self.older_than.is_some_and(|max| entry.last_used != 0 && age < max) { return false; }
+        if self.newer_than.is_some_and(|min| entry.last_used == 0 || age > min) { return false; }
+        self.text.is_empty() || name.to_lowercase().contains(&self.text) || entry.id.to_lowercase().contains(&self.text)
+    }
+}
+pub fn format_age(last_used: u64, now: u64) -> String {
+    if last_used == 0 {
+        return tr("unknown");
+    }
+    let secs = now.saturating_sub(last_used);
+    match secs {
+        0..60 => tr("just now"),
+        60..3600 => trf("{}m ago", &[&(secs / 60).to_string()]),
+        3600..86400 => trf("{}h ago", &[&(secs / 3600).to_string()]),
+        _ => trf("{}d ago", &[&(secs / 86400).to_string()]),
+    }
+}
+fn app_target(id: &str) -> (String, String, bool) {
+    if let Some(info) = gio::DesktopAppInfo::new(id) {
+        let exec = info.commandline().map(|p| p.to_string_lossy().into_owned()).unwrap_or_default();
+        return (exec, info.name().to_string(), info.boolean("Terminal"));
+    }
+    let installed = !id.ends_with(".desktop") && id.split_whitespace().next().is_some_and(find_in_path);
+    (if installed { id.to_string() } else { String::new() }, id.to_string(), false)
+}
+fn entry_item(entry: &HistoryEntry, power: &[PowerOption]) -> AppItem {
+    let id = entry.id.clone();
+    let power_exec = || power.iter().position(|o| history_key(None, &o.command) == (entry.source, entry.id.clone())).map(|i| format!("POWER:{}", i)).unwrap_or_default();
+    let mut name = id.clone();
+    let mut terminal = false;
+    let (exec, icon, desktop_id) = match entry.source {
+        HistorySource::App => {
+            let (exec, app_name, term) = app_target(&id);
+            (name, terminal) = (app_name, term);
+            (exec, "\u{f0008}", id.as_str())
+        }
+        HistorySource::Web => (format!("xdg-open {}", id), "\u{f059f}", "web"),
+        HistorySource::File => (format!("OPEN_PATH:{}", id), "\u{f0214}", "file"),
+        HistorySource::Run => (format!("RUN:{}", id), "\u{f018d}", "run"),
+        HistorySource::Command => (id.clone(), "\u{f0bd}", "command"),
+        HistorySource::Session => (power_exec(), "\u{f0425}", "system"),
+        HistorySource::Other => (power_exec(), "\u{f02da}", "system"),
+    };
+    AppItem {
+        name,
+        exec,
+        terminal,
+        icon: icon.to_string(),
+        desktop_id: desktop_id.to_string(),
+        ..Default::default()
+    }
+}
+pub fn check_history(query: &str, history: &HistoryStore, known: &[&AppItem], power: &[PowerOption]) -> Vec<AppItem> {
+    let filter = HistoryFilter::parse(query);
+    let known: HashMap<HistoryKey, &AppItem> = known.iter().map(|a| (a.history_key(), *a)).collect();
+    let now = now();
+    let mut entries: Vec<(&HistoryEntry, AppItem)> = history.iter()
+        .map(|e| {
+            let item = known.get(&(e.source, e.id.clone())).map(|a| AppItem { pin: None, ..(*a).clone() }).unwrap_or_else(|| entry_item(e, power));
+            (e, item)
+        })
+        .filter(|(e, item)| filter.matches(e, &item.name, now))
+        .collect();
+    entries.sort_by(|a, b| b.0.last_used.cmp(&a.0.last_used).then_with(|| b.0.count.cmp(&a.0.count)));
+    entries.into_iter().map(|(e, mut item)| {
+        item.history_entry = Some((e.source, e.id.clone()));
+        item.subtitle = trf("{0} · used {1}× · {2}", &[&e.source.label(), &e.count.to_string(), &format_age(e.last_used, now)]);
+        item
+    }).collect()
+}
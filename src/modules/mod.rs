@@ -3,4 +3,9 @@ pub mod color_picker;
 pub mod power_menu;
 pub mod file_search;
 pub mod web_search;
-pub mod app_edit;
\ No newline at end of file
+pub mod app_edit;
+pub mod run_command;
+pub mod path_index;
+pub mod categories;
+pub mod history_view;
+pub mod browser_data;
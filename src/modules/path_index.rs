@@ -0,0 +1,87 @@
+use crate::config::{find_override, get_cache_dir, load_custom_overrides};
+use crate::search::{exec_basename, AppItem};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::time::UNIX_EPOCH;
+#[derive(Serialize, Deserialize, Default)]
+struct PathCache {
+    dirs: Vec<(String, u64)>,
+    binaries: Vec<String>,
+}
+fn path_dirs() -> Vec<PathBuf> {
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).collect())
+        .unwrap_or_default()
+}
+fn dir_stamps() -> Vec<(String, u64)> {
+    path_dirs()
+        .into_iter()
+        .map(|dir| {
+            let mtime = std::fs::metadata(&dir)
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            (dir.to_string_lossy().to_string(), mtime)
+        })
+        .collect()
+}
+pub fn scan_path() -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut bins: Vec<String> = path_dirs()
+        .into_iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.filter_map(|e| e.ok()))
+        .filter(|entry| {
+            entry.metadata()
+                .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+                .unwrap_or(false)
+        })
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| seen.insert(name.clone()))
+        .collect();
+    bins.sort();
+    bins
+}
+pub fn load_path_binaries() -> Vec<String> {
+    let path = get_cache_dir().join("path_index.json");
+    let stamps = dir_stamps();
+    let cached: Option<PathCache> = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok());
+    if let Some(cache) = cached.filter(|c| c.dirs == stamps) {
+        return cache.binaries;
+    }
+    let cache = PathCache { dirs: stamps, binaries: scan_path() };
+    let _ = std::fs::create_dir_all(get_cache_dir());
+    if let Ok(content) = serde_json::to_string(&cache) {
+        let _ = std::fs::write(path, content);
+    }
+    cache.binaries
+}
+pub fn get_path_apps(apps: &[AppItem], show_hidden: bool) -> Vec<AppItem> {
+    let overrides = load_custom_overrides();
+    let known: HashSet<String> = apps.iter().filter_map(|a| exec_basename(&a.exec)).collect();
+    load_path_binaries()
+        .into_iter()
+        .filter(|bin| !known.contains(bin))
+        .filter(|bin| bin.chars().all(|c| c.is_alphanumeric() || "-_.+@".contains(c)))
+        .filter(|bin| find_override(&overrides, bin).and_then(|c| c.hidden).unwrap_or(false) == show_hidden)
+        .map(|bin| {
+            let custom = find_override(&overrides, &bin);
+            AppItem {
+                name: custom.and_then(|c| c.name.clone()).unwrap_or_else(|| bin.clone()),
+                terminal: custom.and_then(|c| c.terminal).unwrap_or(false),
+                icon: custom.and_then(|c| c.icon.clone()).unwrap_or_else(|| "\u{f489}".to_string()),
+                desktop_id: "path".to_string(),
+                pin: custom.and_then(|c| c.pin),
+                keywords: custom.and_then(|c| c.keywords.clone()).unwrap_or_default().iter().map(|k| k.to_lowercase()).collect(),
+                exec: bin,
+                ..Default::default()
+            }
+        })
+        .collect()
+}
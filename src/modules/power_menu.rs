@@ -1,8 +1,18 @@
-use crate::config::PowerOption;
+use crate::config::{PowerConfirm, PowerOption};
+use crate::controller::launch_app;
+use crate::i18n::tr;
+use crate::ui::{LauncherState, SelectionMode};
+use crate::wm::session::{self, SessionAction};
 use gtk4::prelude::*;
 use gtk4::{Align, ApplicationWindow, Box, Button, Orientation};
+use std::cell::{Cell, RefCell};
 use std::process::Command;
-pub fn create_power_bar(options: &[PowerOption], window: &ApplicationWindow) -> Box {
+use std::rc::Rc;
+pub struct PendingPower {
+    pub index: usize,
+    pub timer: Option<glib::SourceId>,
+}
+pub fn create_power_bar(options: &[PowerOption]) -> Box {
     let bar = Box::builder()
         .orientation(Orientation::Horizontal)
         .halign(Align::Center)
@@ -11,16 +21,112 @@ pub fn create_power_bar(options: &[PowerOption], window: &ApplicationWindow) ->
         .build();
     bar.add_css_class("power-bar");
     for opt in options {
-        let btn = Button::builder().label(&opt.icon).has_frame(false).build();
+        let btn = Button::builder().label(&opt.icon).has_frame(false).tooltip_text(opt.tooltip_text()).build();
         btn.add_css_class("power-btn");
         btn.add_css_class(&opt.class);
-        let win_clone = window.clone();
-        let cmd_str = opt.command.clone();
-        btn.connect_clicked(move |_| {
-            let _ = Command::new("sh").arg("-c").arg(&cmd_str).spawn();
-            win_clone.close();
-        });
         bar.append(&btn);
     }
     bar
 }
+pub fn setup_power_bar_logic(p_box: &Box, window: &ApplicationWindow, state: &Rc<RefCell<LauncherState>>) {
+    let mut i = 0;
+    let mut iter = p_box.first_child();
+    while let Some(child) = iter {
+        if let Ok(btn) = child.clone().downcast::<Button>() {
+            let (st, win) = (state.clone(), window.clone());
+            btn.connect_clicked(move |_| activate_power_option(i, &st, &win, false));
+        }
+        iter = child.next_sibling();
+        i += 1;
+    }
+}
+fn power_button(state: &LauncherState, index: usize) -> Option<Button> {
+    let mut iter = state.power_box.as_ref()?.first_child();
+    for _ in 0..index {
+        iter = iter?.next_sibling();
+    }
+    iter?.downcast::<Button>().ok()
+}
+pub fn cancel_pending_power(state: &mut LauncherState) {
+    let Some(pending) = state.pending_power.take() else { return; };
+    if let Some(timer) = pending.timer {
+        timer.remove();
+    }
+    if let (Some(btn), Some(opt)) = (power_button(state, pending.index), state.power_options.get(pending.index)) {
+        btn.set_label(&opt.icon);
+        btn.set_tooltip_text(Some(opt.tooltip_text()));
+        btn.remove_css_class("confirming");
+    }
+}
+fn execute_power_option(state: &Rc<RefCell<LauncherState>>, index: usize, window: &ApplicationWindow) {
+    {
+        let mut sh = state.borrow_mut();
+        let Some(opt) = sh.power_options.get(index).cloned() else { return; };
+        let fol = sh.theme_config.focus_on_launch;
+        let term_cmd = sh.theme_config.terminal.clone();
+        launch_app(&opt.command, false, &mut sh.history, None, fol, &term_cmd, &[]);
+    }
+    window.close();
+}
+pub fn activate_power_option(index: usize, state: &Rc<RefCell<LauncherState>>, window: &ApplicationWindow, from_search: bool) {
+    let mut sh = state.borrow_mut();
+    let Some(mut opt) = sh.power_options.get(index).cloned() else { return; };
+    if from_search && opt.confirm == PowerConfirm::None && opt.is_destructive() {
+        opt.confirm = PowerConfirm::Prompt;
+    }
+    let confirming = sh.pending_power.as_ref().map(|p| p.index == index).unwrap_or(false);
+    cancel_pending_power(&mut sh);
+    if confirming || opt.confirm == PowerConfirm::None {
+        drop(sh);
+        execute_power_option(state, index, window);
+        return;
+    }
+    sh.mode = SelectionMode::Power;
+    sh.power_index = index;
+    let mut pi = 0;
+    let mut p_iter = sh.power_box.as_ref().and_then(|b| b.first_child());
+    while let Some(child) = p_iter {
+        if pi == index { child.add_css_class("selected"); } else { child.remove_css_class("selected"); }
+        p_iter = child.next_sibling();
+        pi += 1;
+    }
+    let Some(btn) = power_button(&sh, index) else { return; };
+    btn.add_css_class("confirming");
+    let timer = match opt.confirm {
+        PowerConfirm::Countdown(secs) => {
+            btn.set_label(&secs.to_string());
+            btn.set_tooltip_text(Some(&tr("Press again to run now, Esc to cancel")));
+            let remaining = Cell::new(secs);
+            let (st, win) = (state.clone(), window.clone());
+            Some(glib::timeout_add_seconds_local(1, move || {
+                let left = remaining.get().saturating_sub(1);
+                remaining.set(left);
+                if left > 0 {
+                    btn.set_label(&left.to_string());
+                    return glib::ControlFlow::Continue;
+                }
+                if let Ok(mut sh) = st.try_borrow_mut() {
+                    if let Some(pending) = sh.pending_power.as_mut() {
+                        pending.timer = None;
+                    }
+                    cancel_pending_power(&mut sh);
+                }
+                execute_power_option(&st, index, &win);
+                glib::ControlFlow::Break
+            }))
+        }
+        _ => {
+            btn.set_label("\u{f00c}");
+            btn.set_tooltip_text(Some(&tr("Press again to confirm, Esc to cancel")));
+            None
+        }
+    };
+    sh.pending_power = Some(PendingPower { index, timer });
+}
+pub fn run_power_command(command: &str) {
+    if let Some(action) = command.strip_prefix("SESSION:").and_then(SessionAction::from_name) {
+        session::run(&*crate::wm::detect(), action);
+    } else {
+        let _ = Command::new("sh").arg("-c").arg(command).spawn();
+    }
+}
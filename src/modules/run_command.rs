@@ -0,0 +1,66 @@
+use crate::modules::path_index::scan_path;
+use crate::i18n::trf;
+use crate::history::{HistorySource, HistoryStore};
+use crate::search::AppItem;
+use std::collections::HashSet;
+use std::sync::LazyLock;
+static PATH_EXECUTABLES: LazyLock<Vec<String>> = LazyLock::new(scan_path);
+pub fn path_executables() -> &'static [String] {
+    &PATH_EXECUTABLES
+}
+pub fn check_run(query: &str, history: &HistoryStore) -> Vec<AppItem> {
+    let cmd = query.trim();
+    let mut results = Vec::new();
+    let mut seen = HashSet::new();
+    if !cmd.is_empty() {
+        seen.insert(cmd.to_string());
+        results.push(AppItem {
+            name: trf("Run `{}`", &[cmd]),
+            exec: format!("RUN:{}", cmd),
+            terminal: false,
+            icon: "\u{f018d}".to_string(),
+            desktop_id: "run".to_string(),
+            ..Default::default()
+        });
+        results.push(AppItem {
+            name: trf("Run `{}` in terminal", &[cmd]),
+            exec: format!("RUN:{}", cmd),
+            terminal: true,
+            icon: "\u{f489}".to_string(),
+            desktop_id: "run".to_string(),
+            ..Default::default()
+        });
+    }
+    let mut past: Vec<(&str, u32)> = history.iter_source(HistorySource::Run)
+        .map(|e| (e.id.as_str(), e.count))
+        .filter(|(c, _)| c.contains(cmd))
+        .collect();
+    past.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    for (c, _) in past.into_iter().take(10) {
+        if !seen.insert(c.to_string()) { continue; }
+        results.push(AppItem {
+            name: c.to_string(),
+            exec: format!("RUN:{}", c),
+            terminal: false,
+            icon: "\u{f02da}".to_string(),
+            desktop_id: "run".to_string(),
+            ..Default::default()
+        });
+    }
+    if !cmd.is_empty() && !cmd.contains(char::is_whitespace) {
+        let mut completions: Vec<&String> = path_executables().iter().filter(|b| b.starts_with(cmd)).collect();
+        completions.sort_by_key(|b| b.len());
+        for bin in completions.into_iter().take(20) {
+            if !seen.insert(bin.clone()) { continue; }
+            results.push(AppItem {
+                name: bin.clone(),
+                exec: format!("RUN:{}", bin),
+                terminal: false,
+                icon: "\u{f018d}".to_string(),
+                desktop_id: "run".to_string(),
+                ..Default::default()
+            });
+        }
+    }
+    results
+}
@@ -1,63 +1,198 @@
+use crate::config::{BrowserRule, SearchEngine};
+use crate::history::{HistorySource, HistoryStore};
 use crate::search::AppItem;
+use crate::i18n::{tr, trf};
+use crate::utils::{encode_url, percent_decode, shell_quote};
+use gio::prelude::*;
 use regex::Regex;
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
+use std::process::Command;
 
-pub fn check_web(query: &str, history: &HashMap<String, u32>, _url_re: &Regex, search_engine: &str) -> Vec<AppItem> {
+pub fn find_engine<'a>(engines: &'a [SearchEngine], key: &str) -> Option<&'a SearchEngine> {
+    let key = key.to_lowercase();
+    engines.iter().find(|e| e.keyword == key).or_else(|| engines.iter().find(|e| e.id == key || e.name.to_lowercase() == key))
+}
+fn search_query<'a>(engines: &'a [SearchEngine], url: &str) -> Option<(&'a SearchEngine, String)> {
+    engines.iter().find_map(|e| e.query_from_url(url).map(|q| (e, q)))
+}
+pub fn search_target<'a>(query: &'a str, engines: &'a [SearchEngine], search_engine: &str) -> Option<(&'a SearchEngine, &'a str, bool)> {
+    let default_engine = find_engine(engines, search_engine).or(engines.first())?;
+    if let Some(rest) = query.strip_prefix('!') {
+        let (keyword, q) = rest.split_once(' ')?;
+        return find_engine(engines, keyword).map(|e| (e, q.trim(), true));
+    }
+    let raw = query.strip_prefix('?')?.trim_start();
+    Some(match raw.split_once(' ').and_then(|(kw, rest)| find_engine(engines, kw).map(|e| (e, rest.trim()))) {
+        Some((engine, rest)) => (engine, rest, true),
+        None => (default_engine, raw.trim(), false),
+    })
+}
+thread_local! {
+    static SUGGESTION_CACHE: RefCell<HashMap<String, Vec<String>>> = RefCell::new(HashMap::new());
+}
+pub type SuggestionProvider = fn(&str, u64) -> Option<String>;
+pub fn curl_provider(url: &str, timeout_ms: u64) -> Option<String> {
+    Command::new("curl")
+        .args(["-fsSL", "--max-time", &format!("{:.1}", timeout_ms as f64 / 1000.0), url])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).into_owned())
+}
+pub fn parse_suggestions(body: &str) -> Option<Vec<String>> {
+    let value = serde_json::from_str::<serde_json::Value>(body).ok()?;
+    let list = value.get(1)?.as_array()?;
+    Some(list.iter().filter_map(|s| s.as_str().map(str::to_string)).collect())
+}
+pub fn request_suggestions(provider: SuggestionProvider, url: &str, timeout_ms: u64) -> Option<Vec<String>> {
+    provider(url, timeout_ms).as_deref().and_then(parse_suggestions)
+}
+pub fn cached_suggestions(url: &str) -> Option<Vec<String>> {
+    SUGGESTION_CACHE.with(|c| c.borrow().get(url).cloned())
+}
+fn cache_suggestions(url: String, suggestions: Option<Vec<String>>) -> bool {
+    let Some(list) = suggestions else { return false; };
+    SUGGESTION_CACHE.with(|c| c.borrow_mut().insert(url, list));
+    true
+}
+pub fn fetch_suggestions(provider: SuggestionProvider, url: String, timeout_ms: u64, done: impl FnOnce() + 'static) {
+    glib::spawn_future_local(async move {
+        let request = url.clone();
+        let suggestions = gio::spawn_blocking(move || request_suggestions(provider, &request, timeout_ms)).await.ok().flatten();
+        if cache_suggestions(url, suggestions) {
+            done();
+        }
+    });
+}
+pub fn suggestion_items(engine: &SearchEngine, query: &str, suggestions: &[String]) -> Vec<AppItem> {
+    suggestions.iter()
+        .filter(|s| !s.eq_ignore_ascii_case(query))
+        .take(8)
+        .map(|s| AppItem {
+            name: s.clone(),
+            exec: format!("xdg-open {}", engine.url_for(s)),
+            terminal: false,
+            icon: engine.icon.clone(),
+            desktop_id: "web".to_string(),
+            subtitle: trf("{} suggestion", &[&engine.name]),
+            ..Default::default()
+        })
+        .collect()
+}
+pub fn link_url(item: &AppItem) -> Option<&str> {
+    item.exec.strip_prefix("xdg-open ").filter(|u| u.starts_with("http://") || u.starts_with("https://"))
+}
+pub fn browser_choices(url: &str) -> Vec<AppItem> {
+    gio::AppInfo::all_for_type("x-scheme-handler/https")
+        .into_iter()
+        .filter_map(|app| {
+            let id = app.id()?;
+            Some(AppItem {
+                name: app.display_name().to_string(),
+                exec: format!("OPEN_WITH:{} {}", id, url),
+                terminal: false,
+                icon: "\u{f059f}".to_string(),
+                desktop_id: "browser".to_string(),
+                system_icon: app.icon(),
+                subtitle: url.to_string(),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+pub fn browser_command(rules: &[BrowserRule], url: &str) -> Option<String> {
+    let rule = rules.iter().find(|r| r.matches(url))?;
+    let browser = match rule.browser.strip_suffix(".desktop").map(|_| gio::DesktopAppInfo::new(&rule.browser)) {
+        Some(app) => app?.commandline()?.to_string_lossy()
+            .split_whitespace()
+            .filter(|t| !t.starts_with('%') && !t.starts_with("@@"))
+            .collect::<Vec<_>>()
+            .join(" "),
+        None => rule.browser.clone(),
+    };
+    Some(format!("{} {} {}", browser, rule.args, shell_quote(url)))
+}
+pub fn check_web(query: &str, history: &HistoryStore, _url_re: &Regex, engines: &[SearchEngine], search_engine: &str) -> Vec<AppItem> {
     let mut results = Vec::new();
     let mut seen_names = HashSet::new();
+    let Some(default_engine) = find_engine(engines, search_engine).or(engines.first()) else { return results; };
 
-    let (base_url, engine_name) = match search_engine {
-        "startpage" => ("https://www.startpage.com/do/dsearch?q=", "Startpage"),
-        "duckduckgo" | "ddg" => ("https://duckduckgo.com/?q=", "DuckDuckGo"),
-        "bing" => ("https://www.bing.com/search?q=", "Bing"),
-        "ecosia" => ("https://www.ecosia.org/search?q=", "Ecosia"),
-        "qwant" => ("https://www.qwant.com/?q=", "Qwant"),
-        _ => ("https://www.google.com/search?q=", "Google"),
-    };
+    if query.starts_with('!') {
+        if let Some((engine, q, _)) = search_target(query, engines, search_engine).filter(|(_, q, _)| !q.is_empty()) {
+            results.push(AppItem {
+                name: trf("Search {} for '{}'", &[&engine.name, q]),
+                exec: format!("xdg-open {}", engine.url_for(q)),
+                terminal: false,
+                icon: engine.icon.clone(),
+                desktop_id: "web".to_string(),
+                system_icon: None,
+                ..Default::default()
+            });
+        }
+        return results;
+    }
 
-    if query.starts_with('?') {
-        let q = query[1..].trim();
+    if let Some((engine, q, bang)) = search_target(query, engines, search_engine) {
         if !q.is_empty() {
-            let name = format!("Search {} for '{}'", engine_name, q);
+            let name = trf("Search {} for '{}'", &[&engine.name, q]);
             seen_names.insert(name.clone());
-            let url = format!("{}{}", base_url, q.replace(' ', "+"));
             results.push(AppItem {
                 name,
-                exec: format!("xdg-open {}", url),
+                exec: format!("xdg-open {}", engine.url_for(q)),
                 terminal: false,
-                icon: "\u{f002}".to_string(),
+                icon: engine.icon.clone(),
                 desktop_id: "web".to_string(),
                 system_icon: None,
+                ..Default::default()
             });
         }
-        
-        let sub = q.to_lowercase();
-        let mut h: Vec<_> = history.iter()
-            .filter(|(u, _)| u.contains("?q="))
-            .filter(|(u, _)| sub.is_empty() || u.to_lowercase().contains(&sub))
-            .collect();
-        h.sort_by(|a, b| b.1.cmp(a.1).then_with(|| b.0.cmp(a.0)));
-        
-        for (u, _) in h.into_iter() {
-            if results.len() >= 10 { break; }
-            if let Some(pos) = u.find("?q=") {
-                let q_val = &u[pos+3..];
-                let display_q = q_val.split('&').next().unwrap_or(q_val).replace('+', " ");
-                let name = format!("Search: {}", display_q);
-                
-                if seen_names.contains(&name) { continue; }
-                seen_names.insert(name.clone());
-
+        if !bang && !q.contains(' ') {
+            let prefix = q.to_lowercase();
+            for e in engines.iter().filter(|e| e.keyword.starts_with(&prefix) || e.name.to_lowercase().starts_with(&prefix)) {
                 results.push(AppItem {
-                    name,
-                    exec: u.clone(),
+                    name: e.name.clone(),
+                    exec: format!("ENGINE:{}", e.keyword),
                     terminal: false,
-                    icon: "\u{f002}".to_string(),
-                    desktop_id: "web".to_string(),
-                    system_icon: None,
+                    icon: e.icon.clone(),
+                    desktop_id: "engine".to_string(),
+                    subtitle: format!("?{}", e.keyword),
+                    ..Default::default()
                 });
             }
         }
+
+        let sub = q.to_lowercase();
+        let mut h: Vec<_> = history.iter_source(HistorySource::Web)
+            .filter_map(|e| search_query(engines, &e.id).map(|(eng, q)| (&e.id, e.count, eng, q)))
+            .filter(|(_, _, eng, _)| !bang || std::ptr::eq(*eng, engine))
+            .filter(|(_, _, _, q)| sub.is_empty() || q.to_lowercase().contains(&sub))
+            .collect();
+        h.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| b.0.cmp(a.0)));
+
+        let mut shown = 0;
+        for (u, _, eng, display_q) in h.into_iter() {
+            if shown >= 10 { break; }
+            let name = if std::ptr::eq(eng, default_engine) {
+                trf("Search: {}", &[&display_q])
+            } else {
+                trf("{0}: {1}", &[&eng.name, &display_q])
+            };
+
+            if seen_names.contains(&name) { continue; }
+            seen_names.insert(name.clone());
+            shown += 1;
+
+            results.push(AppItem {
+                name,
+                exec: format!("xdg-open {}", u),
+                terminal: false,
+                icon: eng.icon.clone(),
+                desktop_id: "web".to_string(),
+                system_icon: None,
+                ..Default::default()
+            });
+        }
         return results;
     }
 
@@ -65,9 +200,9 @@ pub fn check_web(query: &str, history: &HashMap<String, u32>, _url_re: &Regex, s
         let sub = query[1..].trim();
         
         if !sub.is_empty() {
-            let name = format!("Open '{}'", sub);
+            let name = trf("Open '{}'", &[sub]);
             seen_names.insert(name.clone());
-            let url = if sub.contains("://") { sub.to_string() } else { format!("https://{}", sub) };
+            let url = encode_url(&if sub.contains("://") { sub.to_string() } else { format!("https://{}", sub) });
             results.push(AppItem {
                 name,
                 exec: format!("xdg-open {}", url),
@@ -75,20 +210,21 @@ pub fn check_web(query: &str, history: &HashMap<String, u32>, _url_re: &Regex, s
                 icon: "\u{f059f}".to_string(),
                 desktop_id: "web".to_string(),
                 system_icon: None,
+                ..Default::default()
             });
         }
 
         let sub_low = sub.to_lowercase();
-        let mut h: Vec<_> = history.iter()
-            .filter(|(u, _)| u.starts_with("xdg-open http") && !u.contains("?q=")) 
+        let mut h: Vec<_> = history.iter_source(HistorySource::Web)
+            .map(|e| (&e.id, e.count))
+            .filter(|(u, _)| u.starts_with("http") && search_query(engines, u).is_none())
             .filter(|(u, _)| sub_low.is_empty() || u.to_lowercase().contains(&sub_low))
             .collect();
-        h.sort_by(|a, b| b.1.cmp(a.1));
+        h.sort_by_key(|e| std::cmp::Reverse(e.1));
         for (u, _) in h {
-            let display = u.trim_start_matches("xdg-open ")
+            let display = percent_decode(u.trim_start_matches("xdg-open ")
                            .trim_start_matches("https://")
-                           .trim_start_matches("http://")
-                           .to_string();
+                           .trim_start_matches("http://"));
             
             let name = display.clone();
             if seen_names.contains(&name) { continue; }
@@ -96,26 +232,102 @@ pub fn check_web(query: &str, history: &HashMap<String, u32>, _url_re: &Regex, s
 
             results.push(AppItem {
                 name,
-                exec: u.clone(),
+                exec: format!("xdg-open {}", u),
                 terminal: false,
                 icon: "\u{f059f}".to_string(),
                 desktop_id: "web".to_string(),
                 system_icon: None,
+                ..Default::default()
             });
         }
         return results;
     }
 
     if _url_re.is_match(query) || query.starts_with("http") {
-        let url = if query.contains("://") { query.to_string() } else { format!("https://{}", query) };
+        let url = encode_url(&if query.contains("://") { query.to_string() } else { format!("https://{}", query) });
         results.push(AppItem {
-            name: "Open Link".to_string(),
+            name: tr("Open Link"),
             exec: format!("xdg-open {}", url),
             terminal: false,
             icon: "\u{f059f}".to_string(),
             desktop_id: "web".to_string(),
             system_icon: None,
+            ..Default::default()
         });
     }
     results
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    fn serve_once(status: &'static str, body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0; 1024];
+            let _ = stream.read(&mut buf);
+            let response = format!("HTTP/1.1 {}\r\nContent-Type: application/x-suggestions+json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", status, body.len(), body);
+            let _ = stream.write_all(response.as_bytes());
+        });
+        format!("http://{}/complete?q=rust", addr)
+    }
+    fn tcp_provider(url: &str, _timeout_ms: u64) -> Option<String> {
+        let rest = url.strip_prefix("http://")?;
+        let (host, path) = rest.split_once('/')?;
+        let mut stream = TcpStream::connect(host).ok()?;
+        stream.write_all(format!("GET /{} HTTP/1.0\r\nHost: {}\r\n\r\n", path, host).as_bytes()).ok()?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).ok()?;
+        let (head, body) = response.split_once("\r\n\r\n")?;
+        head.split_whitespace().nth(1).filter(|&code| code == "200")?;
+        Some(body.to_string())
+    }
+    fn fake_provider(url: &str, _timeout_ms: u64) -> Option<String> {
+        match url {
+            "fake://ok" => Some(r#"["rust",["rust lang","rustup"]]"#.to_string()),
+            "fake://html" => Some("<html></html>".to_string()),
+            _ => None,
+        }
+    }
+    #[test]
+    fn parses_opensearch_suggestions() {
+        assert_eq!(parse_suggestions(r#"["q",["a",1,"b"]]"#), Some(vec!["a".to_string(), "b".to_string()]));
+        assert_eq!(parse_suggestions(r#"["q"]"#), None);
+        assert_eq!(parse_suggestions("not json"), None);
+    }
+    #[test]
+    fn requests_go_through_the_provider() {
+        assert_eq!(request_suggestions(fake_provider, "fake://ok", 100), Some(vec!["rust lang".to_string(), "rustup".to_string()]));
+        assert_eq!(request_suggestions(fake_provider, "fake://html", 100), None);
+        assert_eq!(request_suggestions(fake_provider, "fake://down", 100), None);
+    }
+    #[test]
+    fn fetches_opensearch_suggestions_from_local_server() {
+        let url = serve_once("200 OK", r#"["rust",["rust lang","rust gtk","rustup"]]"#);
+        assert_eq!(request_suggestions(tcp_provider, &url, 2000), Some(vec!["rust lang".to_string(), "rust gtk".to_string(), "rustup".to_string()]));
+        let url = serve_once("500 Internal Server Error", "oops");
+        assert_eq!(request_suggestions(tcp_provider, &url, 2000), None);
+    }
+    #[test]
+    #[ignore = "requires curl"]
+    fn curl_provider_fetches_from_local_server() {
+        let url = serve_once("200 OK", r#"["rust",["rust lang"]]"#);
+        assert_eq!(request_suggestions(curl_provider, &url, 2000), Some(vec!["rust lang".to_string()]));
+        let url = serve_once("500 Internal Server Error", "oops");
+        assert_eq!(request_suggestions(curl_provider, &url, 2000), None);
+    }
+    #[test]
+    fn only_successful_responses_are_cached() {
+        assert!(!cache_suggestions("http://failed".to_string(), None));
+        assert_eq!(cached_suggestions("http://failed"), None);
+        assert!(cache_suggestions("http://empty".to_string(), parse_suggestions(r#"["q",[]]"#)));
+        assert_eq!(cached_suggestions("http://empty"), Some(Vec::new()));
+        assert!(cache_suggestions("fake://ok".to_string(), request_suggestions(fake_provider, "fake://ok", 100)));
+        assert_eq!(cached_suggestions("fake://ok"), Some(vec!["rust lang".to_string(), "rustup".to_string()]));
+        assert!(!cache_suggestions("fake://down".to_string(), request_suggestions(fake_provider, "fake://down", 100)));
+        assert_eq!(cached_suggestions("fake://down"), None);
+    }
+}
@@ -1,11 +1,42 @@
-use crate::config::{load_custom_overrides, PowerOption};
+use crate::i18n::tr;
+use crate::history::{history_key, HistoryKey, HistoryStore};
+use crate::config::{find_override, load_custom_commands, load_custom_overrides, CustomApp, PowerOption, ThemeConfig};
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
 use gio::prelude::*;
 use gio::{AppInfo, DesktopAppInfo};
 use glib::prelude::Cast;
+use std::collections::HashMap;
 use std::process::Command;
 use std::sync::{LazyLock, Mutex};
 use std::time::{Duration, Instant};
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum AppSource {
+    #[default]
+    Native,
+    Flatpak,
+    Snap,
+}
+impl AppSource {
+    pub fn detect(filename: Option<&str>, exec: &str) -> Self {
+        let path = filename.unwrap_or_default();
+        if path.contains("/flatpak/exports/") || flatpak_app_id(exec).is_some() {
+            AppSource::Flatpak
+        } else if path.contains("/snapd/desktop/") || exec.contains("/snap/bin/") {
+            AppSource::Snap
+        } else {
+            AppSource::Native
+        }
+    }
+    pub fn badge(&self) -> Option<&'static str> {
+        match self {
+            AppSource::Native => None,
+            AppSource::Flatpak => Some("flatpak"),
+            AppSource::Snap => Some("snap"),
+        }
+    }
+}
+#[derive(Clone, Debug, Default)]
 pub struct AppItem {
     pub name: String,
     pub exec: String,
@@ -13,10 +44,27 @@ pub struct AppItem {
     pub icon: String,
     pub desktop_id: String,
     pub system_icon: Option<gio::Icon>,
+    pub keywords: Vec<String>,
+    pub confirm: bool,
+    pub untranslated_name: String,
+    pub generic_name: String,
+    pub comment: String,
+    pub categories: Vec<String>,
+    pub exec_name: String,
+    pub pin: Option<u32>,
+    pub source: AppSource,
+    pub wm_class: String,
+    pub subtitle: String,
+    pub history_entry: Option<HistoryKey>,
+}
+impl AppItem {
+    pub fn history_key(&self) -> HistoryKey {
+        self.history_entry.clone().unwrap_or_else(|| history_key(Some(&self.desktop_id), &self.exec))
+    }
 }
-pub fn get_apps(show_hidden: bool) -> Vec<AppItem> {
+pub fn get_apps(show_hidden: bool, config: &ThemeConfig) -> Vec<AppItem> {
     let overrides = load_custom_overrides();
-    AppInfo::all().into_iter()
+    let mut apps: Vec<AppItem> = AppInfo::all().into_iter()
         .filter(|app| {
             let id = app.id().map(|i| i.to_string()).unwrap_or_else(|| app.name().to_string());
             let custom_hidden = overrides.get(&id).and_then(|c| c.hidden);
@@ -32,11 +80,15 @@ pub fn get_apps(show_hidden: bool) -> Vec<AppItem> {
             let id = app.id().map(|i| i.to_string()).unwrap_or_else(|| app.name().to_string());
             let mut name = app.name().to_string();
             let mut icon = None;
-            let matched_custom = overrides.get(&id)
-                .or_else(|| if id.ends_with(".desktop") { overrides.get(&id[..id.len() - 8]) } else { None })
-                .or_else(|| overrides.get(&name));
+            let matched_custom = find_override(&overrides, &id).or_else(|| overrides.get(&name));
             let mut sys_icon = app.icon();
+            let desktop = app.clone().downcast::<DesktopAppInfo>().ok();
+            let mut terminal = desktop.as_ref().map(|d| d.boolean("Terminal")).unwrap_or(false);
+            let mut keywords: Vec<String> = desktop.as_ref().map(|d| d.keywords().iter().map(|k| k.to_lowercase()).collect()).unwrap_or_default();
+            let pin = matched_custom.and_then(|c| c.pin);
             if let Some(custom) = matched_custom {
+                if let Some(t) = custom.terminal { terminal = t; }
+                keywords.extend(custom.keywords.iter().flatten().map(|k| k.to_lowercase()));
                 if let Some(n) = &custom.name { name = n.clone(); }
                 if let Some(i) = &custom.icon { icon = Some(i.clone()); }
                 if let Some(si) = &custom.system_icon {
@@ -47,17 +99,249 @@ pub fn get_apps(show_hidden: bool) -> Vec<AppItem> {
                     };
                 }
             }
+            let exec = app.commandline().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
             AppItem {
                 icon: icon.unwrap_or_else(|| get_nerd_icon(&name).to_string()),
                 name,
-                exec: app.commandline().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
-                terminal: app.downcast::<DesktopAppInfo>().map(|d| d.boolean("Terminal")).unwrap_or(false),
+                untranslated_name: desktop.as_ref().and_then(|d| d.string("Name")).map(|n| n.to_string()).unwrap_or_default(),
+                generic_name: desktop.as_ref().and_then(|d| d.generic_name()).map(|g| g.to_string()).unwrap_or_default(),
+                comment: app.description().map(|c| c.to_string()).unwrap_or_default(),
+                categories: desktop.as_ref().and_then(|d| d.categories())
+                    .map(|c| c.split(';').filter(|c| !c.is_empty()).map(|c| c.to_lowercase()).collect())
+                    .unwrap_or_default(),
+                exec_name: exec_basename(&exec).unwrap_or_default(),
+                source: AppSource::detect(desktop.as_ref().and_then(|d| d.filename()).as_ref().and_then(|f| f.to_str()), &exec),
+                exec,
+                terminal,
                 desktop_id: id,
                 system_icon: sys_icon,
+                keywords,
+                pin,
+                wm_class: desktop.as_ref().and_then(|d| d.startup_wm_class()).map(|c| c.to_lowercase()).unwrap_or_default(),
+                ..Default::default()
             }
         })
+        .collect();
+    apps.extend(get_custom_entries(&overrides, show_hidden));
+    resolve_duplicates(apps, &config.duplicates)
+}
+fn exec_target(app: &AppItem) -> String {
+    if let Some(app_id) = flatpak_app_id(&app.exec) {
+        return app.exec.split_whitespace()
+            .find_map(|t| t.strip_prefix("--command="))
+            .map(|c| c.rsplit('/').next().unwrap_or(c).to_string())
+            .unwrap_or_else(|| app_id.rsplit('.').next().unwrap_or(&app_id).to_string())
+            .to_lowercase();
+    }
+    app.exec_name.to_lowercase()
+}
+fn source_rank(source: AppSource, policy: &str) -> u8 {
+    let preferred = match policy {
+        "prefer-native" => AppSource::Native,
+        "prefer-flatpak" => AppSource::Flatpak,
+        "prefer-snap" => AppSource::Snap,
+        _ => return 0,
+    };
+    if source == preferred { 0 } else if source == AppSource::Native { 1 } else { 2 }
+}
+fn find_root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+fn duplicate_groups(apps: &[AppItem]) -> Vec<Vec<usize>> {
+    let mut parent: Vec<usize> = (0..apps.len()).collect();
+    let mut owner: HashMap<String, usize> = HashMap::new();
+    for (i, app) in apps.iter().enumerate() {
+        if app.exec.is_empty() { continue; }
+        let mut keys = vec![format!("exec:{}:{}", app.name.to_lowercase(), exec_target(app))];
+        if !app.wm_class.is_empty() {
+            keys.push(format!("class:{}", app.wm_class));
+        }
+        for k in keys {
+            match owner.get(&k) {
+                Some(&j) => {
+                    let (a, b) = (find_root(&mut parent, i), find_root(&mut parent, j));
+                    parent[a.max(b)] = a.min(b);
+                }
+                None => { owner.insert(k, i); }
+            }
+        }
+    }
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut group_of: HashMap<usize, usize> = HashMap::new();
+    for i in 0..apps.len() {
+        let root = find_root(&mut parent, i);
+        match group_of.get(&root) {
+            Some(&g) => groups[g].push(i),
+            None => {
+                group_of.insert(root, groups.len());
+                groups.push(vec![i]);
+            }
+        }
+    }
+    groups
+}
+fn resolve_duplicates(apps: Vec<AppItem>, policy: &str) -> Vec<AppItem> {
+    let groups = duplicate_groups(&apps);
+    let mut slots: Vec<Option<AppItem>> = apps.into_iter().map(Some).collect();
+    let mut result = Vec::with_capacity(slots.len());
+    for group in groups {
+        if group.len() == 1 || policy == "show-both" {
+            let multiple = group.len() > 1;
+            for i in group {
+                let Some(mut app) = slots[i].take() else { continue; };
+                if multiple {
+                    let source = tr(match app.source {
+                        AppSource::Native => "Native",
+                        AppSource::Flatpak => "Flatpak",
+                        AppSource::Snap => "Snap",
+                    });
+                    app.subtitle = format!("{} · {}", source, app.desktop_id.trim_end_matches(".desktop"));
+                }
+                result.push(app);
+            }
+            continue;
+        }
+        let winner = *group.iter().min_by_key(|&&i| slots[i].as_ref().map(|a| source_rank(a.source, policy)).unwrap_or(u8::MAX)).unwrap_or(&group[0]);
+        let Some(mut app) = slots[winner].take() else { continue; };
+        for i in group {
+            let Some(other) = slots[i].take() else { continue; };
+            for k in other.keywords {
+                if !app.keywords.contains(&k) { app.keywords.push(k); }
+            }
+            if app.pin.is_none() { app.pin = other.pin; }
+        }
+        result.push(app);
+    }
+    result
+}
+pub fn app_execs() -> Vec<(String, String)> {
+    let overrides = load_custom_overrides();
+    AppInfo::all().into_iter()
+        .map(|app| {
+            let id = app.id().map(|i| i.to_string()).unwrap_or_else(|| app.name().to_string());
+            (id, app.commandline().map(|p| p.to_string_lossy().to_string()).unwrap_or_default())
+        })
+        .chain([false, true].into_iter().flat_map(|hidden| get_custom_entries(&overrides, hidden)).map(|a| (a.desktop_id, a.exec)))
         .collect()
 }
+fn get_custom_entries(overrides: &HashMap<String, CustomApp>, show_hidden: bool) -> Vec<AppItem> {
+    overrides.iter()
+        .filter(|(id, c)| id.starts_with("custom-") && c.hidden.unwrap_or(false) == show_hidden)
+        .filter_map(|(id, c)| {
+            let exec = c.exec.clone().filter(|e| !e.is_empty())?;
+            let name = c.name.clone().unwrap_or_else(|| id.trim_start_matches("custom-").to_string());
+            let system_icon: Option<gio::Icon> = match c.system_icon.as_deref() {
+                None | Some("nerd") => None,
+                Some(path) if path.starts_with('/') => Some(gio::FileIcon::new(&gio::File::for_path(path)).upcast()),
+                Some(themed) => Some(gio::ThemedIcon::new(themed).upcast()),
+            };
+            Some(AppItem {
+                icon: c.icon.clone().unwrap_or_else(|| get_nerd_icon(&name).to_string()),
+                name,
+                terminal: c.terminal.unwrap_or(false),
+                desktop_id: id.clone(),
+                system_icon,
+                keywords: c.keywords.iter().flatten().map(|k| k.to_lowercase()).collect(),
+                exec_name: exec_basename(&exec).unwrap_or_default(),
+                exec,
+                pin: c.pin,
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+pub fn sort_default(apps: &mut [AppItem], history: &HistoryStore) {
+    apps.sort_by(|a, b| {
+        match (a.pin, b.pin) {
+            (Some(pa), Some(pb)) => pa.cmp(&pb),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => history.count_item(b).cmp(&history.count_item(a)),
+        }
+    });
+}
+pub fn get_custom_commands() -> Vec<AppItem> {
+    load_custom_commands()
+        .into_iter()
+        .filter(|c| !c.name.is_empty() && !c.command.is_empty())
+        .map(|c| AppItem {
+            icon: c.icon.unwrap_or_else(|| "\u{f489}".to_string()),
+            exec: format!("sh -c {}", crate::utils::shell_quote(&c.command)),
+            terminal: c.terminal,
+            desktop_id: "command".to_string(),
+            system_icon: None,
+            keywords: c.keywords.iter().map(|k| k.to_lowercase()).collect(),
+            confirm: c.confirm,
+            name: c.name,
+            ..Default::default()
+        })
+        .collect()
+}
+pub fn score_item(matcher: &SkimMatcherV2, item: &AppItem, query: &str) -> Option<i64> {
+    let weighted = |fields: &mut dyn Iterator<Item = &String>, weight: i64| {
+        fields.filter(|f| !f.is_empty()).filter_map(|f| matcher.fuzzy_match(f, query)).max().map(|sc| sc * weight / 10)
+    };
+    [
+        weighted(&mut [&item.name, &item.untranslated_name].into_iter(), 10),
+        weighted(&mut std::iter::once(&item.generic_name), 8),
+        weighted(&mut item.keywords.iter(), 7),
+        weighted(&mut std::iter::once(&item.exec_name), 6),
+        weighted(&mut item.categories.iter(), 4),
+        weighted(&mut std::iter::once(&item.comment).filter(|c| matches_word_prefix(c, query)), 3),
+    ].into_iter().flatten().max()
+}
+fn matches_word_prefix(text: &str, query: &str) -> bool {
+    let (text, query) = (text.to_lowercase(), query.trim().to_lowercase());
+    !query.is_empty() && text.match_indices(&query).any(|(i, _)| !text[..i].chars().next_back().is_some_and(char::is_alphanumeric))
+}
+pub fn exec_basename(exec: &str) -> Option<String> {
+    let mut tokens = exec.split_whitespace().map(|t| t.trim_matches(|c| c == '"' || c == '\''));
+    let mut prog = tokens.next()?;
+    if prog.rsplit('/').next() == Some("env") {
+        prog = tokens.find(|t| !t.contains('=') && !t.starts_with('-'))?;
+    }
+    prog.rsplit('/').next().filter(|b| !b.is_empty()).map(|b| b.to_string())
+}
+pub fn flatpak_app_id(exec: &str) -> Option<String> {
+    let mut tokens = exec.split_whitespace();
+    tokens.find(|t| t.rsplit('/').next() == Some("flatpak"))?;
+    if tokens.next()? != "run" { return None; }
+    tokens.find(|t| !t.starts_with('-') && !t.starts_with("@@")).map(|t| t.to_string())
+}
+pub fn window_match_keys(desktop_id: &str, exec: &str) -> Vec<String> {
+    static GENERIC_BINARIES: &[&str] = &["sh", "bash", "env", "flatpak", "python", "python3", "java"];
+    let mut keys = Vec::new();
+    if let Some(class) = find_override(&load_custom_overrides(), desktop_id).and_then(|c| c.window_class.clone()) {
+        keys.push(class);
+    }
+    if let Some(info) = DesktopAppInfo::new(desktop_id) {
+        if let Some(class) = info.startup_wm_class() {
+            keys.push(class.to_string());
+        }
+    }
+    keys.push(desktop_id.trim_end_matches(".desktop").to_string());
+    if let Some(app_id) = flatpak_app_id(exec) {
+        keys.push(app_id);
+        if let Some(cmd) = exec.split_whitespace().find_map(|t| t.strip_prefix("--command=")) {
+            keys.push(cmd.rsplit('/').next().unwrap_or(cmd).to_string());
+        }
+    }
+    if let Some((snap_name, _)) = desktop_id.trim_end_matches(".desktop").split_once('_').filter(|_| exec.contains("/snap/")) {
+        keys.push(snap_name.to_string());
+    }
+    if let Some(bin) = exec_basename(exec) {
+        if !GENERIC_BINARIES.contains(&bin.as_str()) {
+            keys.push(bin);
+        }
+    }
+    keys.retain(|k| !k.is_empty());
+    keys.dedup();
+    keys
+}
 pub fn get_nerd_icon(name: &str) -> &'static str {
     static ICON_MAP: &[(&[&str], &str)] = &[
         (&["terminal", "kitty", "alacritty", "foot", "console"], "\u{f489}"),
@@ -135,6 +419,7 @@ pub fn get_clipboard_history() -> Vec<AppItem> {
                     icon: ic,
                     desktop_id: "clipboard".to_string(),
                     system_icon: None,
+                    ..Default::default()
                 });
             }
         }
@@ -164,6 +449,7 @@ pub fn check_calc(query: &str) -> Option<AppItem> {
             icon: "\u{f00ec}".to_string(),
             desktop_id: "calc".to_string(),
             system_icon: None,
+            ..Default::default()
         });
     }
     let now = Instant::now();
@@ -175,6 +461,7 @@ pub fn check_calc(query: &str) -> Option<AppItem> {
             icon: "\u{f00ec}".to_string(),
             desktop_id: "calc".to_string(),
             system_icon: None,
+            ..Default::default()
         });
     }
     cache.0 = now;
@@ -206,33 +493,74 @@ pub fn check_calc(query: &str) -> Option<AppItem> {
                 icon: "\u{f00ec}".to_string(),
                 desktop_id: "calc".to_string(),
                 system_icon: None,
+                ..Default::default()
             });
         }
     }
     cache.2 = None;
     None
 }
-pub fn check_system_commands(query: &str, options: &[PowerOption]) -> Vec<AppItem> {
-    let mut results = Vec::new();
-    let q = query.to_lowercase();
-    for opt in options {
-        let name = match opt.class.as_str() {
-            "shutdown-btn" => "Shutdown",
-            "reboot-btn" => "Reboot",
-            "logout-btn" => "Log Out",
-            "theme-btn" => "Toggle Theme",
-            _ => continue,
-        };
-        if name.to_lowercase() == q {
-             results.push(AppItem {
-                name: name.to_string(),
-                exec: opt.command.clone(),
+pub fn check_system_commands(query: &str, options: &[PowerOption], matcher: &SkimMatcherV2) -> Vec<(i64, AppItem)> {
+    let q = query.trim();
+    if q.is_empty() { return Vec::new(); }
+    options.iter().enumerate()
+        .filter_map(|(i, opt)| {
+            let keyword_score = opt.keywords.iter().filter_map(|k| matcher.fuzzy_match(k, q)).max().map(|sc| sc * 7 / 10);
+            let score = matcher.fuzzy_match(&opt.name, q).max(keyword_score)?;
+            Some((score, AppItem {
+                name: opt.name.clone(),
+                exec: format!("POWER:{}", i),
                 terminal: false,
                 icon: opt.icon.clone(),
                 desktop_id: "system".to_string(),
                 system_icon: None,
-            });
-        }
+                ..Default::default()
+            }))
+        })
+        .collect()
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn exec_basename_skips_paths_quotes_and_env() {
+        assert_eq!(exec_basename("/usr/bin/firefox %u").as_deref(), Some("firefox"));
+        assert_eq!(exec_basename("'/opt/tool/bin/tool' --flag").as_deref(), Some("tool"));
+        assert_eq!(exec_basename("env GDK_BACKEND=x11 gimp %U").as_deref(), Some("gimp"));
+        assert_eq!(exec_basename("/usr/bin/env LANG=C").as_deref(), None);
+        assert_eq!(exec_basename("").as_deref(), None);
+    }
+    #[test]
+    fn flatpak_app_id_finds_the_application() {
+        assert_eq!(flatpak_app_id("/usr/bin/flatpak run --branch=stable --arch=x86_64 --command=gimp org.gimp.GIMP @@u %U @@").as_deref(), Some("org.gimp.GIMP"));
+        assert_eq!(flatpak_app_id("flatpak run org.mozilla.firefox").as_deref(), Some("org.mozilla.firefox"));
+        assert_eq!(flatpak_app_id("flatpak install org.mozilla.firefox"), None);
+        assert_eq!(flatpak_app_id("firefox %u"), None);
+    }
+    #[test]
+    fn comments_match_on_word_prefixes_only() {
+        assert!(matches_word_prefix("Browse the World Wide Web", "wor"));
+        assert!(matches_word_prefix("Edit images (GIMP)", "gimp"));
+        assert!(!matches_word_prefix("Browse the World Wide Web", "rowse"));
+        assert!(!matches_word_prefix("Browse the World Wide Web", "bw"));
+        assert!(!matches_word_prefix("Anything", " "));
     }
-    results
-}
\ No newline at end of file
+    fn app(name: &str, exec: &str, wm_class: &str) -> AppItem {
+        AppItem { name: name.to_string(), exec: exec.to_string(), wm_class: wm_class.to_string(), ..Default::default() }
+    }
+    #[test]
+    fn duplicate_groups_merge_through_shared_keys() {
+        let apps = vec![
+            app("Editor", "editor %F", ""),
+            app("Other", "other", "org.example.Editor"),
+            app("Editor", "/usr/bin/editor", "org.example.Editor"),
+            app("Unrelated", "unrelated", ""),
+        ];
+        assert_eq!(duplicate_groups(&apps), vec![vec![0, 1, 2], vec![3]]);
+    }
+    #[test]
+    fn entries_without_exec_are_never_grouped() {
+        let apps = vec![app("A", "", ""), app("A", "", "")];
+        assert_eq!(duplicate_groups(&apps), vec![vec![0], vec![1]]);
+    }
+}
@@ -1,9 +1,12 @@
-use crate::config::{load_state, ThemeConfig, PowerOption};
-use crate::search::{get_apps, AppItem};
+use crate::history::HistoryStore;
+use crate::i18n::tr;
+use crate::config::{load_state, CustomApp, OutputGeometry, ThemeConfig, PowerOption};
+use crate::search::{app_execs, get_apps, get_custom_commands, sort_default, AppItem};
 use crate::controller::{setup_key_controller, setup_search_logic, setup_window_events};
 use crate::modules::color_picker::{create_color_picker, setup_color_picker_logic};
 use crate::modules::app_launcher::{create_app_list, initialize_list_view};
-use crate::modules::power_menu::create_power_bar;
+use crate::modules::path_index::get_path_apps;
+use crate::modules::power_menu::{create_power_bar, setup_power_bar_logic, PendingPower};
 use crate::wm::{self, WindowManager}; 
 use gtk4::prelude::*;
 use gtk4::{
@@ -29,6 +32,7 @@ pub enum SelectionMode {
     Power,
     Clipboard,
     Color,
+    History,
 }
 #[derive(Clone, Copy, PartialEq, Debug)]
 #[allow(dead_code)]
@@ -36,19 +40,36 @@ pub enum EditingMode {
     None,
     Rename,
     Icon,
+    Keywords,
+    Args,
+    Env,
+    Workdir,
+    NewName,
+    NewExec,
+    NewIcon,
+    NewKeywords,
+    NewTerminal,
+    NewExport,
 }
 pub struct LauncherState {
     pub all_apps: Vec<AppItem>,
     pub filtered_apps: Vec<AppItem>,
+    pub commands: Vec<AppItem>,
+    pub path_apps: Vec<AppItem>,
+    pub pending_confirm: Option<(usize, String)>,
+    pub pending_clear: bool,
     pub clipboard_items: Vec<AppItem>,
     pub app_index: usize,
     pub power_index: usize,
     pub clip_index: usize,
     pub power_options: Vec<PowerOption>,
+    pub power_box: Option<Box>,
+    pub pending_power: Option<PendingPower>,
     pub mode: SelectionMode,
     pub editing_mode: EditingMode,
     pub editing_id: Option<String>,
-    pub history: HashMap<String, u32>,
+    pub new_entry: Option<CustomApp>,
+    pub history: HistoryStore,
     pub show_hidden: bool,
     pub show_hotkeys: bool,
     pub icon_mode: String,
@@ -67,11 +88,23 @@ pub struct LauncherState {
     pub current_hsv: (f64, f64, f64),
     pub current_alpha: f64,
     pub is_syncing: bool,
+    pub layer_shell: bool,
+    pub output_key: String,
+    pub output_origin: (i32, i32),
+    pub window_pos: Option<(i32, i32)>,
+    pub outputs: HashMap<String, OutputGeometry>,
+}
+impl LauncherState {
+    pub fn default_list(&self) -> Vec<AppItem> {
+        let mut apps: Vec<AppItem> = self.all_apps.iter().chain(self.path_apps.iter().filter(|a| a.pin.is_some())).cloned().collect();
+        sort_default(&mut apps, &self.history);
+        apps
+    }
 }
 pub fn create_hotkeys_window(app: &Application, state: &Rc<RefCell<LauncherState>>) {
     let window = ApplicationWindow::builder()
         .application(app)
-        .title("Hotkeys")
+        .title(tr("Hotkeys"))
         .default_width(450)
         .default_height(550)
         .decorated(false)
@@ -93,25 +126,40 @@ pub fn create_hotkeys_window(app: &Application, state: &Rc<RefCell<LauncherState
     root.append(&content);
     let hk_label = Label::builder().build();
     hk_label.add_css_class("app-name");
-    hk_label.set_markup("<span weight='bold' size='xx-large'>Hotkeys</span>");
+    hk_label.set_markup(&format!("<span weight='bold' size='xx-large'>{}</span>", glib::markup_escape_text(&tr("Hotkeys"))));
     content.append(&hk_label);
     let hk_grid = Grid::builder().column_spacing(30).row_spacing(8).build();
     let keys = vec![
-        ("Enter", "Launch / Action"),
-        ("Esc", "Close / Cancel"),
-        ("Tab", "Switch Apps / Power / Color"),
-        ("Up / Down", "Navigate List"),
-        ("Delete", "Remove item from search and link history"),
-        ("Ctrl + Z", "Clipboard History"),
-        ("Ctrl + G", "Color Picker"),
-        ("Ctrl + R", "Rename App"),
-        ("Ctrl + E", "Change Icon"),
-        ("Ctrl + S", "Hide / Show App"),
-        ("Ctrl + H", "Toggle Hidden Apps"),
-        ("Ctrl + I", "Toggle Icon Mode"),
-        (":", "Browser History"),
-        ("?", "Web Search"),
-        ("/", "File Search"),
+        ("Enter", tr("Launch / Action")),
+        ("Shift + Enter", tr("Open Link With...")),
+        ("Esc", tr("Close / Cancel")),
+        ("Tab", tr("Switch Apps / Power / Color")),
+        ("Up / Down", tr("Navigate List")),
+        ("Delete", tr("Remove item from search and link history")),
+        ("Shift + Delete", tr("Remove all filtered history entries")),
+        ("Ctrl + Z", tr("Clipboard History")),
+        ("Ctrl + Shift + H", tr("Usage History")),
+        ("Ctrl + Shift + P", tr("Toggle Incognito (no history)")),
+        ("Ctrl + G", tr("Color Picker")),
+        ("Ctrl + R", tr("Rename App")),
+        ("Ctrl + E", tr("Change Icon")),
+        ("Ctrl + S", tr("Hide / Show App")),
+        ("Ctrl + H", tr("Toggle Hidden Apps")),
+        ("Ctrl + N", tr("Add Custom Entry")),
+        ("Ctrl + K", tr("Edit Keywords")),
+        ("Ctrl + Shift + A", tr("Edit Launch Arguments")),
+        ("Ctrl + Shift + E", tr("Edit Environment")),
+        ("Ctrl + Shift + W", tr("Edit Working Directory")),
+        ("Ctrl + T", tr("Toggle Run in Terminal")),
+        ("Ctrl + Shift + N", tr("Toggle Always New Instance")),
+        ("Ctrl + I", tr("Toggle Icon Mode")),
+        ("Ctrl + P", tr("Pin / Unpin App")),
+        ("Ctrl + Shift + Up / Down", tr("Move Pinned App")),
+        (":", tr("Browser History")),
+        ("?", tr("Web Search")),
+        ("/", tr("File Search")),
+        (">", tr("Run Command")),
+        ("@", tr("Browse Categories")),
     ];
     for (i, (k, d)) in keys.into_iter().enumerate() {
         let k_lbl = Label::builder().label(k).halign(Align::Start).build();
@@ -131,7 +179,7 @@ pub fn create_hotkeys_window(app: &Application, state: &Rc<RefCell<LauncherState
     config_note.add_css_class("app-name");
     content.append(&config_note);
     let dont_show_check = CheckButton::builder()
-        .label("Don't show again")
+        .label(tr("Don't show again"))
         .active(!state.borrow().show_hotkeys)
         .build();
     dont_show_check.add_css_class("app-name");
@@ -140,7 +188,7 @@ pub fn create_hotkeys_window(app: &Application, state: &Rc<RefCell<LauncherState
         st_hk.borrow_mut().show_hotkeys = !btn.is_active();
     });
     content.append(&dont_show_check);
-    let close_hk_btn = Button::builder().label("Close").build();
+    let close_hk_btn = Button::builder().label(tr("Close")).build();
     close_hk_btn.add_css_class("app-name");
     let win_clone = window.clone();
     close_hk_btn.connect_clicked(move |_| win_clone.close());
@@ -148,7 +196,7 @@ pub fn create_hotkeys_window(app: &Application, state: &Rc<RefCell<LauncherState
     window.set_child(Some(&root));
     window.present();
 }
-pub fn build_ui(app: &Application) {
+pub fn build_ui(app: &Application, incognito: bool) {
     crate::config::ensure_config_files();
     let windows = app.windows();
     if !windows.is_empty() {
@@ -160,13 +208,23 @@ pub fn build_ui(app: &Application) {
     let state_saved = load_state();
     let theme_config = ThemeConfig::load();
     let wm = wm::detect(); 
+    let monitor = wm::placement::current_monitor(&*wm, &theme_config);
+    let output_key = monitor.as_ref().map(wm::placement::output_key).unwrap_or_default();
+    let saved_geometry = state_saved.outputs.get(&output_key);
+    let size = saved_geometry.map(|g| (g.width, g.height)).unwrap_or((state_saved.width, state_saved.height));
+    let window_pos = wm::placement::initial_position(&*wm, &theme_config.window_placement, monitor.as_ref(), saved_geometry, size);
+    let output_origin = monitor.as_ref().map(|m| (m.geometry().x(), m.geometry().y())).unwrap_or((0, 0));
     let window = ApplicationWindow::builder()
         .application(app)
         .title("centrum-launcher")
-        .default_width(state_saved.width)
-        .default_height(state_saved.height)
+        .default_width(size.0)
+        .default_height(size.1)
         .decorated(false)
         .build();
+    let layer_shell = wm::layer_shell::init(&window, &theme_config.layer_shell);
+    if let (true, Some((x, y))) = (layer_shell, window_pos) {
+        wm::layer_shell::set_position(&window, monitor.as_ref(), x, y);
+    }
     let overlay = Overlay::new();
     overlay.add_css_class("main-container");
     let (scrolled_window, list_container) = create_app_list();
@@ -190,23 +248,40 @@ pub fn build_ui(app: &Application) {
     let (edit_popup, edit_label, edit_entry) = create_edit_popup();
     overlay.add_overlay(&edit_popup);
     let power_options = theme_config.power_options.clone();
-    let power_box = create_power_bar(&power_options, &window);
+    let power_box = create_power_bar(&power_options);
     overlay.add_overlay(&power_box);
     let (color_box, color_preview, color_square, hue_area, alpha_area, hex_label, rgb_label, hex_copy, rgb_copy) = create_color_picker();
     overlay.add_overlay(&color_box);
     window.set_child(Some(&overlay));
+    let all_apps = get_apps(false, &theme_config);
+    let mut history = state_saved.history;
+    history.migrate_apps(&app_execs());
+    history.prune(theme_config.history_max_entries, theme_config.history_max_age);
+    history.set_ignored(theme_config.history_ignore.clone());
+    history.set_paused(incognito);
+    if incognito {
+        search_entry.add_css_class("incognito");
+    }
+    let path_apps = if theme_config.path_index { get_path_apps(&all_apps, false) } else { Vec::new() };
     let state = Rc::new(RefCell::new(LauncherState {
-        all_apps: get_apps(false),
+        all_apps,
         filtered_apps: Vec::new(),
+        commands: get_custom_commands(),
+        path_apps,
+        pending_confirm: None,
+        pending_clear: false,
         clipboard_items: Vec::new(),
         app_index: 0,
         power_index: 0,
         clip_index: 0,
         power_options,
+        power_box: Some(power_box.clone()),
+        pending_power: None,
         mode: SelectionMode::Apps,
         editing_mode: EditingMode::None,
         editing_id: None,
-        history: state_saved.history,
+        new_entry: None,
+        history,
         show_hidden: false,
         show_hotkeys: state_saved.show_hotkeys,
         icon_mode: theme_config.icon_mode.clone(),
@@ -225,6 +300,11 @@ pub fn build_ui(app: &Application) {
         current_hsv: (0.0, 0.0, 1.0),
         current_alpha: 1.0,
         is_syncing: false,
+        layer_shell,
+        output_key,
+        output_origin,
+        window_pos,
+        outputs: state_saved.outputs,
     }));
     if state_saved.show_hotkeys {
         create_hotkeys_window(app, &state);
@@ -232,6 +312,7 @@ pub fn build_ui(app: &Application) {
     initialize_list_view(&list_container, &window, &state);
     setup_search_logic(&search_entry, &list_container, &scrolled_window, &power_box, &window, &state);
     setup_color_picker_logic(&state);
+    setup_power_bar_logic(&power_box, &window, &state);
     setup_key_controller(
         &window, 
         &search_entry, 
@@ -257,7 +338,7 @@ fn create_edit_popup() -> (Box, Label, gtk4::Entry) {
         .visible(false)
         .build();
     popup.add_css_class("edit-popup");
-    let label = Label::builder().label("Edit").css_classes(["edit-popup-label"]).build();
+    let label = Label::builder().label(tr("Edit")).css_classes(["edit-popup-label"]).build();
     let entry = gtk4::Entry::builder().has_frame(false).css_classes(["edit-popup-entry"]).build();
     gtk4::prelude::EntryExt::set_alignment(&entry, 0.5);
     popup.append(&label);
@@ -277,7 +358,7 @@ pub fn update_visuals(
     let top_pad = scroll_settings.top_padding;
     let bot_pad = scroll_settings.bottom_padding;
     let target_idx = match state.mode {
-        SelectionMode::Apps => state.app_index,
+        SelectionMode::Apps | SelectionMode::History => state.app_index,
         SelectionMode::Clipboard => state.clip_index,
         SelectionMode::Power => 99999, 
         SelectionMode::Color => 99999,
@@ -288,7 +369,7 @@ pub fn update_visuals(
     scroll_w.set_visible(state.mode != SelectionMode::Color);
     p_box.set_visible(state.mode != SelectionMode::Color);
     while let Some(child) = iter {
-        if (state.mode == SelectionMode::Apps || state.mode == SelectionMode::Clipboard)
+        if matches!(state.mode, SelectionMode::Apps | SelectionMode::Clipboard | SelectionMode::History)
             && i == target_idx
         {
             child.add_css_class("selected");
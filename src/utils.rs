@@ -1,3 +1,60 @@
+pub fn find_in_path(bin: &str) -> bool {
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(bin).is_file()))
+        .unwrap_or(false)
+}
+pub fn detect_terminal() -> String {
+    if let Ok(term) = std::env::var("TERMINAL") {
+        if !term.is_empty() { return term; }
+    }
+    ["kitty", "alacritty", "foot", "wezterm", "ghostty", "gnome-terminal", "konsole", "xfce4-terminal", "xterm"]
+        .into_iter()
+        .find(|t| find_in_path(t))
+        .unwrap_or("xterm")
+        .to_string()
+}
+pub fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+pub fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{:02X}", b));
+        }
+    }
+    out
+}
+pub fn encode_url(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = String::with_capacity(s.len());
+    for (i, &b) in bytes.iter().enumerate() {
+        let escaped = b == b'%' && bytes.get(i + 1..i + 3).is_some_and(|h| h.iter().all(u8::is_ascii_hexdigit));
+        if escaped || b.is_ascii_alphanumeric() || b"-._~:/?#[]@!$&'()*+,;=".contains(&b) {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{:02X}", b));
+        }
+    }
+    out
+}
+pub fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3)
+            .filter(|h| h.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|h| u8::from_str_radix(std::str::from_utf8(h).ok()?, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => { out.push(b); i += 3; }
+            (b, _) => { out.push(b); i += 1; }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
 pub fn hsv_to_rgb(h: f64, s: f64, v: f64) -> (f64, f64, f64) {
     let i = (h / 60.0).floor() as i32;
     let f = h / 60.0 - i as f64;
@@ -30,3 +87,33 @@ pub fn rgb_to_hsv(r: f64, g: f64, b: f64) -> (f64, f64, f64) {
     let s = if max == 0.0 { 0.0 } else { delta / max };
     (h, s, max)
 }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn percent_encode_escapes_reserved_and_non_ascii() {
+        assert_eq!(percent_encode("a&b#c%d+e f"), "a%26b%23c%25d%2Be%20f");
+        assert_eq!(percent_encode("grüße"), "gr%C3%BC%C3%9Fe");
+        assert_eq!(percent_encode("safe-._~"), "safe-._~");
+    }
+    #[test]
+    fn percent_decode_round_trips() {
+        for s in ["a&b#c%d+e f", "grüße", "100% sure", "日本語"] {
+            assert_eq!(percent_decode(&percent_encode(s)), s);
+        }
+    }
+    #[test]
+    fn percent_decode_keeps_invalid_escapes() {
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz%2"), "%zz%2");
+        assert_eq!(percent_decode("%+1"), "%+1");
+        assert_eq!(percent_decode("a+b"), "a+b");
+    }
+    #[test]
+    fn encode_url_keeps_structure() {
+        assert_eq!(encode_url("https://example.com/a b?q=1&r=2#top"), "https://example.com/a%20b?q=1&r=2#top");
+        assert_eq!(encode_url("https://example.com/%41%zz"), "https://example.com/%41%25zz");
+        assert_eq!(encode_url("https://de.wikipedia.org/wiki/Straße"), "https://de.wikipedia.org/wiki/Stra%C3%9Fe");
+        assert_eq!(encode_url("https://x.org/\"<>"), "https://x.org/%22%3C%3E");
+    }
+}
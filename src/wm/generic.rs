@@ -13,4 +13,14 @@ impl WindowManager for Generic {
         }
     }
     fn center_cursor_or_window(&self) {}
+    fn focused_output(&self) -> Option<String> {
+        None
+    }
+    fn cursor_position(&self) -> Option<(i32, i32)> {
+        None
+    }
+    fn launcher_position(&self) -> Option<(i32, i32)> {
+        None
+    }
+    fn move_launcher(&self, _x: i32, _y: i32) {}
 }
@@ -1,5 +1,5 @@
 use std::process::Command;
-use super::{WindowManager, WindowInfo};
+use super::{WindowManager, WindowInfo, LAUNCHER_APP_ID};
 pub struct Hyprland;
 impl WindowManager for Hyprland {
     fn get_window_list(&self) -> Vec<WindowInfo> {
@@ -9,12 +9,12 @@ impl WindowManager for Hyprland {
                 if let Some(windows) = json.as_array() {
                     for win in windows {
                         let address = win.get("address").and_then(|a| a.as_str()).unwrap_or_default();
-                        let title = win.get("title").and_then(|t| t.as_str()).unwrap_or_default();
                         let class = win.get("class").and_then(|c| c.as_str()).unwrap_or_default();
+                        let history_id = win.get("focusHistoryID").and_then(|f| f.as_i64()).unwrap_or(-1);
                         list.push(WindowInfo {
                             id: address.to_string(),
-                            title: title.to_string(),
                             app_id: class.to_string(),
+                            last_focused: if history_id >= 0 { u64::MAX - history_id as u64 } else { 0 },
                         });
                     }
                 }
@@ -32,5 +32,42 @@ impl WindowManager for Hyprland {
     fn logout(&self) {
         let _ = Command::new("hyprctl").arg("dispatch").arg("exit").spawn();
     }
-    fn center_cursor_or_window(&self) {}
+    fn center_cursor_or_window(&self) {
+        let _ = Command::new("hyprctl")
+            .arg("--batch")
+            .arg(format!("dispatch focuswindow class:^({})$ ; dispatch centerwindow", LAUNCHER_APP_ID.replace('.', "\\.")))
+            .spawn();
+    }
+    fn focused_output(&self) -> Option<String> {
+        let output = Command::new("hyprctl").arg("monitors").arg("-j").output().ok()?;
+        let json = serde_json::from_slice::<serde_json::Value>(&output.stdout).ok()?;
+        json.as_array()?
+            .iter()
+            .find(|m| m.get("focused").and_then(|f| f.as_bool()).unwrap_or(false))
+            .and_then(|m| m.get("name").and_then(|n| n.as_str()))
+            .map(|n| n.to_string())
+    }
+    fn cursor_position(&self) -> Option<(i32, i32)> {
+        let output = Command::new("hyprctl").arg("cursorpos").arg("-j").output().ok()?;
+        let json = serde_json::from_slice::<serde_json::Value>(&output.stdout).ok()?;
+        Some((json.get("x")?.as_i64()? as i32, json.get("y")?.as_i64()? as i32))
+    }
+    fn launcher_position(&self) -> Option<(i32, i32)> {
+        let output = Command::new("hyprctl").arg("clients").arg("-j").output().ok()?;
+        let json = serde_json::from_slice::<serde_json::Value>(&output.stdout).ok()?;
+        let at = json.as_array()?
+            .iter()
+            .find(|w| w.get("class").and_then(|c| c.as_str()) == Some(LAUNCHER_APP_ID))?
+            .get("at")?
+            .as_array()?
+            .clone();
+        Some((at.first()?.as_i64()? as i32, at.get(1)?.as_i64()? as i32))
+    }
+    fn move_launcher(&self, x: i32, y: i32) {
+        let _ = Command::new("hyprctl")
+            .arg("dispatch")
+            .arg("movewindowpixel")
+            .arg(format!("exact {} {},class:^({})$", x, y, LAUNCHER_APP_ID.replace('.', "\\.")))
+            .spawn();
+    }
 }
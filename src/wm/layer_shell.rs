@@ -0,0 +1,47 @@
+use crate::config::LayerShellSettings;
+#[cfg(feature = "layer-shell")]
+use gtk4_layer_shell::{Edge, KeyboardMode, Layer, LayerShell};
+#[cfg(feature = "layer-shell")]
+pub fn init(window: &gtk4::ApplicationWindow, settings: &LayerShellSettings) -> bool {
+    if !settings.enabled || !gtk4_layer_shell::is_supported() { return false; }
+    window.init_layer_shell();
+    window.set_namespace(Some("centrum-launcher"));
+    window.set_layer(if settings.layer == "top" { Layer::Top } else { Layer::Overlay });
+    window.set_keyboard_mode(if settings.keyboard == "on-demand" { KeyboardMode::OnDemand } else { KeyboardMode::Exclusive });
+    let edges = [
+        (Edge::Top, "top", settings.margin_top),
+        (Edge::Bottom, "bottom", settings.margin_bottom),
+        (Edge::Left, "left", settings.margin_left),
+        (Edge::Right, "right", settings.margin_right),
+    ];
+    for (edge, name, margin) in edges {
+        window.set_anchor(edge, settings.anchor.iter().any(|a| a == name));
+        window.set_margin(edge, margin);
+    }
+    if settings.output != "focused" {
+        if let Some(monitor) = super::placement::find_monitor(&settings.output) {
+            window.set_monitor(Some(&monitor));
+        }
+    }
+    true
+}
+#[cfg(feature = "layer-shell")]
+pub fn set_position(window: &gtk4::ApplicationWindow, monitor: Option<&gtk4::gdk::Monitor>, x: i32, y: i32) {
+    if !window.is_layer_window() { return; }
+    if let Some(monitor) = monitor {
+        window.set_monitor(Some(monitor));
+    }
+    for (edge, anchored, margin) in [(Edge::Top, true, y), (Edge::Left, true, x), (Edge::Bottom, false, 0), (Edge::Right, false, 0)] {
+        window.set_anchor(edge, anchored);
+        window.set_margin(edge, margin);
+    }
+}
+#[cfg(not(feature = "layer-shell"))]
+pub fn init(_window: &gtk4::ApplicationWindow, settings: &LayerShellSettings) -> bool {
+    if settings.enabled {
+        eprintln!("-gtk-layer-shell is set, but centrum-launcher was built without the layer-shell feature");
+    }
+    false
+}
+#[cfg(not(feature = "layer-shell"))]
+pub fn set_position(_window: &gtk4::ApplicationWindow, _monitor: Option<&gtk4::gdk::Monitor>, _x: i32, _y: i32) {}
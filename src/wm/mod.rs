@@ -2,19 +2,26 @@ use std::env;
 pub mod niri;
 pub mod hyprland;
 pub mod generic;
+pub mod layer_shell;
+pub mod placement;
+pub mod session;
 pub trait WindowManager {
     fn get_window_list(&self) -> Vec<WindowInfo>;
     fn focus_window(&self, id: &str);
-    #[allow(dead_code)]
     fn logout(&self);
     fn center_cursor_or_window(&self);
+    fn focused_output(&self) -> Option<String>;
+    fn cursor_position(&self) -> Option<(i32, i32)>;
+    fn launcher_position(&self) -> Option<(i32, i32)>;
+    fn move_launcher(&self, x: i32, y: i32);
 }
 #[derive(Debug, Clone)]
 pub struct WindowInfo {
     pub id: String,
-    pub title: String,
     pub app_id: String,
+    pub last_focused: u64,
 }
+pub const LAUNCHER_APP_ID: &str = "org.centrum.launcher";
 pub fn detect() -> Box<dyn WindowManager> {
     let xdg_current = env::var("XDG_CURRENT_DESKTOP").unwrap_or_default().to_lowercase();
     let session = env::var("DESKTOP_SESSION").unwrap_or_default().to_lowercase();
@@ -27,3 +34,24 @@ pub fn detect() -> Box<dyn WindowManager> {
         Box::new(generic::Generic)
     }
 }
+pub fn focus_app_window(wm: &dyn WindowManager, keys: &[String]) -> bool {
+    let windows: Vec<WindowInfo> = wm.get_window_list()
+        .into_iter()
+        .filter(|w| w.app_id != LAUNCHER_APP_ID)
+        .collect();
+    let active = windows.iter().filter(|w| w.last_focused > 0).max_by_key(|w| w.last_focused).map(|w| w.id.clone());
+    let mut matches: Vec<&WindowInfo> = windows.iter()
+        .filter(|w| !w.app_id.is_empty() && keys.iter().any(|k| k.eq_ignore_ascii_case(&w.app_id)))
+        .collect();
+    if matches.is_empty() { return false; }
+    matches.sort_by(|a, b| match (a.id.parse::<u64>(), b.id.parse::<u64>()) {
+        (Ok(x), Ok(y)) => x.cmp(&y),
+        _ => a.id.cmp(&b.id),
+    });
+    let target = match matches.iter().position(|w| Some(&w.id) == active.as_ref()) {
+        Some(i) => matches[(i + 1) % matches.len()],
+        None => matches.iter().max_by_key(|w| w.last_focused).copied().unwrap_or(matches[0]),
+    };
+    wm.focus_window(&target.id);
+    true
+}
@@ -1,6 +1,15 @@
 use std::process::Command;
-use super::{WindowManager, WindowInfo};
+use super::{WindowManager, WindowInfo, LAUNCHER_APP_ID};
 pub struct Niri;
+fn output_origin() -> (i32, i32) {
+    Command::new("niri").arg("msg").arg("--json").arg("focused-output").output().ok()
+        .and_then(|o| serde_json::from_slice::<serde_json::Value>(&o.stdout).ok())
+        .and_then(|json| {
+            let logical = json.get("logical")?;
+            Some((logical.get("x")?.as_i64()? as i32, logical.get("y")?.as_i64()? as i32))
+        })
+        .unwrap_or((0, 0))
+}
 impl WindowManager for Niri {
     fn get_window_list(&self) -> Vec<WindowInfo> {
         let mut list = Vec::new();
@@ -9,12 +18,23 @@ impl WindowManager for Niri {
                 if let Some(windows) = json.as_array() {
                     for win in windows {
                         if let Some(id) = win.get("id").and_then(|i| i.as_u64()) {
-                            let title = win.get("title").and_then(|t| t.as_str()).unwrap_or_default();
                             let app_id = win.get("app_id").and_then(|a| a.as_str()).unwrap_or_default();
+                            let is_focused = win.get("is_focused").and_then(|f| f.as_bool()).unwrap_or(false);
+                            let last_focused = if is_focused {
+                                u64::MAX
+                            } else {
+                                win.get("focus_timestamp")
+                                    .map(|ts| {
+                                        let secs = ts.get("secs").and_then(|s| s.as_u64()).unwrap_or(0);
+                                        let nanos = ts.get("nanos").and_then(|n| n.as_u64()).unwrap_or(0);
+                                        secs.saturating_mul(1_000_000_000).saturating_add(nanos)
+                                    })
+                                    .unwrap_or(0)
+                            };
                             list.push(WindowInfo {
                                 id: id.to_string(),
-                                title: title.to_string(),
                                 app_id: app_id.to_string(),
+                                last_focused,
                             });
                         }
                     }
@@ -38,4 +58,37 @@ impl WindowManager for Niri {
     fn center_cursor_or_window(&self) {
         let _ = Command::new("niri").arg("msg").arg("action").arg("center-column").spawn();
     }
+    fn focused_output(&self) -> Option<String> {
+        let output = Command::new("niri").arg("msg").arg("--json").arg("focused-output").output().ok()?;
+        let json = serde_json::from_slice::<serde_json::Value>(&output.stdout).ok()?;
+        json.get("name").and_then(|n| n.as_str()).map(|n| n.to_string())
+    }
+    fn cursor_position(&self) -> Option<(i32, i32)> {
+        None
+    }
+    fn launcher_position(&self) -> Option<(i32, i32)> {
+        let output = Command::new("niri").arg("msg").arg("--json").arg("windows").output().ok()?;
+        let json = serde_json::from_slice::<serde_json::Value>(&output.stdout).ok()?;
+        let pos = json.as_array()?
+            .iter()
+            .find(|w| w.get("app_id").and_then(|a| a.as_str()) == Some(LAUNCHER_APP_ID))?
+            .get("layout")?
+            .get("tile_pos_in_workspace_view")?
+            .as_array()?
+            .clone();
+        let (ox, oy) = output_origin();
+        Some((ox + pos.first()?.as_f64()? as i32, oy + pos.get(1)?.as_f64()? as i32))
+    }
+    fn move_launcher(&self, x: i32, y: i32) {
+        let (ox, oy) = output_origin();
+        let _ = Command::new("niri")
+            .arg("msg")
+            .arg("action")
+            .arg("move-floating-window")
+            .arg("-x")
+            .arg((x - ox).to_string())
+            .arg("-y")
+            .arg((y - oy).to_string())
+            .spawn();
+    }
 }
@@ -0,0 +1,49 @@
+use super::WindowManager;
+use crate::config::{OutputGeometry, ThemeConfig};
+use gtk4::gdk::Monitor;
+use gtk4::prelude::*;
+pub fn monitors() -> Vec<Monitor> {
+    let Some(display) = gtk4::gdk::Display::default() else { return Vec::new(); };
+    let list = display.monitors();
+    (0..list.n_items())
+        .filter_map(|i| list.item(i).and_then(|m| m.downcast::<Monitor>().ok()))
+        .collect()
+}
+pub fn find_monitor(connector: &str) -> Option<Monitor> {
+    monitors().into_iter().find(|m| m.connector().map(|c| c == connector).unwrap_or(false))
+}
+pub fn current_monitor(wm: &dyn WindowManager, config: &ThemeConfig) -> Option<Monitor> {
+    let configured = (config.layer_shell.enabled && config.layer_shell.output != "focused")
+        .then(|| config.layer_shell.output.clone());
+    configured
+        .or_else(|| wm.focused_output())
+        .and_then(|c| find_monitor(&c))
+        .or_else(|| monitors().into_iter().next())
+}
+pub fn output_key(monitor: &Monitor) -> String {
+    let connector = monitor.connector().map(|c| c.to_string()).unwrap_or_default();
+    match monitor.model() {
+        Some(model) => format!("{}/{}", connector, model),
+        None => connector,
+    }
+}
+pub fn initial_position(
+    wm: &dyn WindowManager,
+    placement: &str,
+    monitor: Option<&Monitor>,
+    saved: Option<&OutputGeometry>,
+    size: (i32, i32),
+) -> Option<(i32, i32)> {
+    match placement {
+        "last" => saved.filter(|g| g.x >= 0 && g.y >= 0).map(|g| (g.x, g.y)),
+        "cursor" => {
+            let (cx, cy) = wm.cursor_position()?;
+            let geo = monitor?.geometry();
+            Some((
+                (cx - geo.x() - size.0 / 2).clamp(0, (geo.width() - size.0).max(0)),
+                (cy - geo.y() - size.1 / 2).clamp(0, (geo.height() - size.1).max(0)),
+            ))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,84 @@
+use super::WindowManager;
+use glib::prelude::ToVariant;
+use std::process::Command;
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SessionAction {
+    Logout,
+    Lock,
+    Suspend,
+    Hibernate,
+    Reboot,
+    Poweroff,
+    RebootFirmware,
+}
+impl SessionAction {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "logout" | "log-out" => Some(Self::Logout),
+            "lock" => Some(Self::Lock),
+            "suspend" | "sleep" => Some(Self::Suspend),
+            "hibernate" => Some(Self::Hibernate),
+            "reboot" | "restart" => Some(Self::Reboot),
+            "poweroff" | "shutdown" => Some(Self::Poweroff),
+            "reboot-firmware" | "firmware" => Some(Self::RebootFirmware),
+            _ => None,
+        }
+    }
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Logout => "Log Out",
+            Self::Lock => "Lock",
+            Self::Suspend => "Suspend",
+            Self::Hibernate => "Hibernate",
+            Self::Reboot => "Reboot",
+            Self::Poweroff => "Shutdown",
+            Self::RebootFirmware => "Reboot to Firmware",
+        }
+    }
+}
+fn logind_call(object_path: &str, interface: &str, method: &str, parameters: Option<glib::Variant>) -> bool {
+    let Ok(conn) = gio::bus_get_sync(gio::BusType::System, gio::Cancellable::NONE) else { return false; };
+    conn.call_sync(
+        Some("org.freedesktop.login1"),
+        object_path,
+        interface,
+        method,
+        parameters.as_ref(),
+        None,
+        gio::DBusCallFlags::ALLOW_INTERACTIVE_AUTHORIZATION,
+        5000,
+        gio::Cancellable::NONE,
+    )
+    .is_ok()
+}
+fn manager_call(method: &str, parameters: Option<glib::Variant>) -> bool {
+    logind_call("/org/freedesktop/login1", "org.freedesktop.login1.Manager", method, parameters)
+}
+pub fn run(wm: &dyn WindowManager, action: SessionAction) {
+    let ok = match action {
+        SessionAction::Logout => {
+            wm.logout();
+            true
+        }
+        SessionAction::Lock => logind_call("/org/freedesktop/login1/session/auto", "org.freedesktop.login1.Session", "Lock", None),
+        SessionAction::Suspend => manager_call("Suspend", Some((true,).to_variant())),
+        SessionAction::Hibernate => manager_call("Hibernate", Some((true,).to_variant())),
+        SessionAction::Reboot => manager_call("Reboot", Some((true,).to_variant())),
+        SessionAction::Poweroff => manager_call("PowerOff", Some((true,).to_variant())),
+        SessionAction::RebootFirmware => {
+            manager_call("SetRebootToFirmwareSetup", Some((true,).to_variant()))
+                && manager_call("Reboot", Some((true,).to_variant()))
+        }
+    };
+    if ok { return; }
+    let fallback: &[&str] = match action {
+        SessionAction::Logout => return,
+        SessionAction::Lock => &["loginctl", "lock-session"],
+        SessionAction::Suspend => &["systemctl", "suspend"],
+        SessionAction::Hibernate => &["systemctl", "hibernate"],
+        SessionAction::Reboot => &["systemctl", "reboot"],
+        SessionAction::Poweroff => &["systemctl", "poweroff"],
+        SessionAction::RebootFirmware => &["systemctl", "reboot", "--firmware-setup"],
+    };
+    let _ = Command::new(fallback[0]).args(&fallback[1..]).spawn();
+}